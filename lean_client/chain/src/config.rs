@@ -1,5 +1,5 @@
-/// Core consensus parameters and chain presets
-/// for the Lean Consensus Experimental Chain.
+//! Core consensus parameters and chain presets
+//! for the Lean Consensus Experimental Chain.
 
 /// A value in basis points (1/10000).
 /// Valid range: 0 <= value <= 10000
@@ -12,6 +12,8 @@ impl BasisPoint {
         if value <= Self::MAX { Some(BasisPoint(value)) } else { None }
     }
     #[inline] pub fn get(&self) -> u64 { self.0 }
+    /// This share of `value`, rounded down.
+    #[inline] pub fn of(&self, value: u64) -> u64 { value * self.0 / Self::MAX }
 }
 
 pub const INTERVALS_PER_SLOT: u64 = 4;
@@ -25,6 +27,13 @@ pub const VOTE_DUE_BPS: BasisPoint          = match BasisPoint::new(5_000) { Som
 pub const FAST_CONFIRM_DUE_BPS: BasisPoint  = match BasisPoint::new(7_500) { Some(x) => x, None => panic!() };
 pub const VIEW_FREEZE_CUTOFF_BPS: BasisPoint= match BasisPoint::new(7_500) { Some(x) => x, None => panic!() };
 
+/// Extra weight, as a share of all validators, given to a block that arrives before the reorg cutoff.
+pub const PROPOSER_SCORE_BOOST_BPS: BasisPoint        = match BasisPoint::new(4_000) { Some(x) => x, None => panic!() };
+/// A late head backed by less than this share of validators may be reorged by the next proposer...
+pub const REORG_HEAD_WEIGHT_THRESHOLD_BPS: BasisPoint = match BasisPoint::new(2_000) { Some(x) => x, None => panic!() };
+/// ...as long as its parent is backed by at least this share.
+pub const REORG_PARENT_WEIGHT_THRESHOLD_BPS: BasisPoint = match BasisPoint::new(6_000) { Some(x) => x, None => panic!() };
+
 pub const HISTORICAL_ROOTS_LIMIT: u64   = 1u64 << 18;
 pub const VALIDATOR_REGISTRY_LIMIT: u64 = 1u64 << 12;
 
@@ -37,6 +46,9 @@ pub struct ChainConfig {
     pub vote_due_bps: BasisPoint,
     pub fast_confirm_due_bps: BasisPoint,
     pub view_freeze_cutoff_bps: BasisPoint,
+    pub proposer_score_boost_bps: BasisPoint,
+    pub reorg_head_weight_threshold_bps: BasisPoint,
+    pub reorg_parent_weight_threshold_bps: BasisPoint,
     pub historical_roots_limit: u64,
     pub validator_registry_limit: u64,
 }
@@ -49,6 +61,9 @@ pub const DEVNET_CONFIG: ChainConfig = ChainConfig {
     vote_due_bps: VOTE_DUE_BPS,
     fast_confirm_due_bps: FAST_CONFIRM_DUE_BPS,
     view_freeze_cutoff_bps: VIEW_FREEZE_CUTOFF_BPS,
+    proposer_score_boost_bps: PROPOSER_SCORE_BOOST_BPS,
+    reorg_head_weight_threshold_bps: REORG_HEAD_WEIGHT_THRESHOLD_BPS,
+    reorg_parent_weight_threshold_bps: REORG_PARENT_WEIGHT_THRESHOLD_BPS,
     historical_roots_limit: HISTORICAL_ROOTS_LIMIT,
     validator_registry_limit: VALIDATOR_REGISTRY_LIMIT,
 };
@@ -61,4 +76,9 @@ mod tests {
         assert_eq!(SECONDS_PER_SLOT, 4);
         assert_eq!(SECONDS_PER_INTERVAL, 1);
     }
+    #[test] fn basis_points_take_a_share() {
+        assert_eq!(PROPOSER_REORG_CUTOFF_BPS.of(SLOT_DURATION_MS), 1_000);
+        assert_eq!(PROPOSER_SCORE_BOOST_BPS.of(10), 4);
+        assert_eq!(BasisPoint(10_000).of(7), 7);
+    }
 }
\ No newline at end of file
@@ -0,0 +1,186 @@
+//! Genesis from a config file, so every node of a devnet starts from byte-identical state.
+//!
+//! ```yaml
+//! genesis_time: 1700000000
+//! preset: devnet
+//! validators:
+//!   - root: "0x…"
+//!     public_seed: "0x…"
+//! ```
+use crate::config::{ChainConfig, DEVNET_CONFIG};
+use containers::{
+    block::hash_tree_root,
+    ssz::{SszReadDefault, SszWrite},
+    Block, Bytes32, Checkpoint, PublicKey, State, Uint64,
+};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+pub const STATE_FILE_STEM: &str = "genesis_state";
+pub const BLOCK_FILE_STEM: &str = "genesis_block";
+
+/// Named parameter set the chain runs with.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Preset {
+    #[default]
+    Devnet,
+}
+
+impl Preset {
+    pub fn chain_config(&self) -> &'static ChainConfig {
+        match self {
+            Self::Devnet => &DEVNET_CONFIG,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GenesisConfig {
+    /// genesis timestamp (seconds since UNIX epoch)
+    pub genesis_time: u64,
+    #[serde(default)]
+    pub preset: Preset,
+    /// validator pubkeys, in registry order
+    pub validators: Vec<PublicKey>,
+}
+
+/// On-disk encoding of genesis config, state and block.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    Ssz,
+    Yaml,
+    Json,
+}
+
+impl Format {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Ssz => "ssz",
+            Self::Yaml => "yaml",
+            Self::Json => "json",
+        }
+    }
+
+    pub fn from_path(path: &Path) -> Result<Self, GenesisError> {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("ssz") => Ok(Self::Ssz),
+            Some("yaml" | "yml") => Ok(Self::Yaml),
+            Some("json") => Ok(Self::Json),
+            _ => Err(GenesisError::UnsupportedFormat(path.display().to_string())),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GenesisError {
+    Io(String),
+    Parse(String),
+    Encode(String),
+    UnsupportedFormat(String),
+    NoValidators,
+    TooManyValidators { count: usize, limit: u64 },
+}
+
+impl std::fmt::Display for GenesisError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "I/O error: {}", e),
+            Self::Parse(e) => write!(f, "Invalid genesis data: {}", e),
+            Self::Encode(e) => write!(f, "Encoding failed: {}", e),
+            Self::UnsupportedFormat(path) => write!(f, "Unsupported file format: {}", path),
+            Self::NoValidators => write!(f, "Genesis needs at least one validator"),
+            Self::TooManyValidators { count, limit } =>
+                write!(f, "{} validators exceed the registry limit of {}", count, limit),
+        }
+    }
+}
+
+impl std::error::Error for GenesisError {}
+
+impl GenesisConfig {
+    /// Reads a `.yaml`/`.yml` or `.json` config file.
+    pub fn load(path: &Path) -> Result<Self, GenesisError> {
+        let text = fs::read_to_string(path).map_err(|e| GenesisError::Io(format!("{}: {}", path.display(), e)))?;
+        match Format::from_path(path)? {
+            Format::Yaml => Self::from_yaml_str(&text),
+            Format::Json => Self::from_json_str(&text),
+            Format::Ssz => Err(GenesisError::UnsupportedFormat(path.display().to_string())),
+        }
+    }
+
+    pub fn from_yaml_str(text: &str) -> Result<Self, GenesisError> {
+        serde_yaml::from_str(text).map_err(|e| GenesisError::Parse(e.to_string()))
+    }
+
+    pub fn from_json_str(text: &str) -> Result<Self, GenesisError> {
+        serde_json::from_str(text).map_err(|e| GenesisError::Parse(e.to_string()))
+    }
+
+    pub fn build(&self) -> Result<Genesis, GenesisError> {
+        let limit = self.preset.chain_config().validator_registry_limit;
+        if self.validators.is_empty() {
+            return Err(GenesisError::NoValidators);
+        }
+        if self.validators.len() as u64 > limit {
+            return Err(GenesisError::TooManyValidators { count: self.validators.len(), limit });
+        }
+
+        let state = State::generate_genesis(Uint64(self.genesis_time), &self.validators);
+        let block = state.genesis_block();
+        Ok(Genesis { state, block })
+    }
+}
+
+/// Genesis state and the block every chain is anchored at.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Genesis {
+    pub state: State,
+    pub block: Block,
+}
+
+impl Genesis {
+    /// Root of the genesis block; fork choice starts from here.
+    pub fn anchor_root(&self) -> Bytes32 {
+        hash_tree_root(&self.block)
+    }
+
+    pub fn anchor_checkpoint(&self) -> Checkpoint {
+        Checkpoint { root: self.anchor_root(), slot: self.block.slot }
+    }
+
+    /// Writes `genesis_state.<ext>` and `genesis_block.<ext>` into `dir`.
+    pub fn write(&self, dir: &Path, format: Format) -> Result<(), GenesisError> {
+        fs::create_dir_all(dir).map_err(|e| GenesisError::Io(format!("{}: {}", dir.display(), e)))?;
+        write_file(&dir.join(format!("{}.{}", STATE_FILE_STEM, format.extension())), &self.state, format)?;
+        write_file(&dir.join(format!("{}.{}", BLOCK_FILE_STEM, format.extension())), &self.block, format)
+    }
+
+    /// Reads back what [`Genesis::write`] produced.
+    pub fn read(dir: &Path, format: Format) -> Result<Self, GenesisError> {
+        let state = read_file(&dir.join(format!("{}.{}", STATE_FILE_STEM, format.extension())), format)?;
+        let block = read_file(&dir.join(format!("{}.{}", BLOCK_FILE_STEM, format.extension())), format)?;
+        Ok(Self { state, block })
+    }
+}
+
+fn write_file<T: SszWrite + Serialize>(path: &Path, value: &T, format: Format) -> Result<(), GenesisError> {
+    let bytes = match format {
+        Format::Ssz => value.to_ssz().map_err(|e| GenesisError::Encode(format!("{:?}", e)))?,
+        Format::Yaml => serde_yaml::to_string(value).map_err(|e| GenesisError::Encode(e.to_string()))?.into_bytes(),
+        Format::Json => serde_json::to_vec_pretty(value).map_err(|e| GenesisError::Encode(e.to_string()))?,
+    };
+    fs::write(path, bytes).map_err(|e| GenesisError::Io(format!("{}: {}", path.display(), e)))
+}
+
+fn read_file<T: SszReadDefault + for<'de> Deserialize<'de>>(path: &Path, format: Format) -> Result<T, GenesisError> {
+    let bytes = fs::read(path).map_err(|e| GenesisError::Io(format!("{}: {}", path.display(), e)))?;
+    let parse = |e: String| GenesisError::Parse(format!("{}: {}", path.display(), e));
+    match format {
+        Format::Ssz => T::from_ssz_default(&bytes).map_err(|e| parse(format!("{:?}", e))),
+        Format::Yaml => serde_yaml::from_slice(&bytes).map_err(|e| parse(e.to_string())),
+        Format::Json => serde_json::from_slice(&bytes).map_err(|e| parse(e.to_string())),
+    }
+}
@@ -0,0 +1,101 @@
+use chain::genesis::{Format, Genesis, GenesisConfig, GenesisError, Preset};
+use containers::{block::hash_tree_root, Bytes32, PublicKey, Slot};
+use containers::ssz::H256;
+use std::fs;
+use std::path::PathBuf;
+
+const YAML: &str = r#"
+genesis_time: 1700000000
+preset: devnet
+validators:
+  - root: "0x0101010101010101010101010101010101010101010101010101010101010101"
+    public_seed: "0x0202020202020202020202020202020202020202020202020202020202020202"
+  - root: "0x0303030303030303030303030303030303030303030303030303030303030303"
+    public_seed: "0x0404040404040404040404040404040404040404040404040404040404040404"
+"#;
+
+fn pubkey(byte: u8) -> PublicKey {
+    PublicKey { root: Bytes32(H256::repeat_byte(byte)), public_seed: Bytes32(H256::repeat_byte(byte + 1)) }
+}
+
+fn scratch_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("chain-genesis-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    dir
+}
+
+#[test]
+fn test_yaml_and_json_configs_agree() {
+    let from_yaml = GenesisConfig::from_yaml_str(YAML).unwrap();
+    assert_eq!(from_yaml.genesis_time, 1_700_000_000);
+    assert_eq!(from_yaml.preset, Preset::Devnet);
+    assert_eq!(from_yaml.validators, vec![pubkey(1), pubkey(3)]);
+
+    let json = serde_json::to_string(&from_yaml).unwrap();
+    assert_eq!(GenesisConfig::from_json_str(&json).unwrap(), from_yaml);
+}
+
+#[test]
+fn test_build_anchors_block_to_state() {
+    let genesis = GenesisConfig::from_yaml_str(YAML).unwrap().build().unwrap();
+
+    assert_eq!(genesis.state.config.genesis_time, 1_700_000_000);
+    assert_eq!(genesis.state.config.num_validators, 2);
+    assert_eq!(genesis.state.validator_pubkey(containers::ValidatorIndex(1)).unwrap(), &pubkey(3));
+    assert_eq!(genesis.block.slot, Slot(0));
+    assert_eq!(genesis.block.state_root, hash_tree_root(&genesis.state));
+    assert_eq!(genesis.anchor_root(), hash_tree_root(&genesis.block));
+}
+
+#[test]
+fn test_write_and_read_are_byte_identical() {
+    let genesis = GenesisConfig::from_yaml_str(YAML).unwrap().build().unwrap();
+
+    for format in [Format::Ssz, Format::Yaml, Format::Json] {
+        let first = scratch_dir(&format!("first-{}", format.extension()));
+        let second = scratch_dir(&format!("second-{}", format.extension()));
+        genesis.write(&first, format).unwrap();
+        GenesisConfig::from_yaml_str(YAML).unwrap().build().unwrap().write(&second, format).unwrap();
+
+        for stem in ["genesis_state", "genesis_block"] {
+            let file = format!("{}.{}", stem, format.extension());
+            assert_eq!(fs::read(first.join(&file)).unwrap(), fs::read(second.join(&file)).unwrap(), "{}", file);
+        }
+        assert_eq!(Genesis::read(&first, format).unwrap(), genesis);
+
+        fs::remove_dir_all(&first).unwrap();
+        fs::remove_dir_all(&second).unwrap();
+    }
+}
+
+#[test]
+fn test_load_picks_format_from_extension() {
+    let dir = scratch_dir("load");
+    fs::create_dir_all(&dir).unwrap();
+    let expected = GenesisConfig::from_yaml_str(YAML).unwrap();
+
+    fs::write(dir.join("config.yml"), YAML).unwrap();
+    fs::write(dir.join("config.json"), serde_json::to_string(&expected).unwrap()).unwrap();
+    fs::write(dir.join("config.toml"), "").unwrap();
+
+    assert_eq!(GenesisConfig::load(&dir.join("config.yml")).unwrap(), expected);
+    assert_eq!(GenesisConfig::load(&dir.join("config.json")).unwrap(), expected);
+    assert!(matches!(GenesisConfig::load(&dir.join("config.toml")), Err(GenesisError::UnsupportedFormat(_))));
+    assert!(matches!(GenesisConfig::load(&dir.join("missing.yaml")), Err(GenesisError::Io(_))));
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_invalid_configs_are_rejected() {
+    let empty = GenesisConfig { genesis_time: 0, preset: Preset::Devnet, validators: vec![] };
+    assert_eq!(empty.build(), Err(GenesisError::NoValidators));
+
+    let too_many = GenesisConfig { genesis_time: 0, preset: Preset::Devnet, validators: vec![pubkey(1); 4097] };
+    assert_eq!(too_many.build(), Err(GenesisError::TooManyValidators { count: 4097, limit: 4096 }));
+
+    let unknown_field = format!("{}extra: 1\n", YAML);
+    assert!(matches!(GenesisConfig::from_yaml_str(&unknown_field), Err(GenesisError::Parse(_))));
+    let unknown_preset = YAML.replace("devnet", "mainnet");
+    assert!(matches!(GenesisConfig::from_yaml_str(&unknown_preset), Err(GenesisError::Parse(_))));
+}
@@ -1,4 +1,4 @@
-use crate::{Bytes32, Slot,  SignedVote, ValidatorIndex};
+use crate::{Bytes32, Signature, Slot,  SignedVote, ValidatorIndex};
 use ssz::PersistentList as List;
 use ssz::{SszHash};
 use ssz_derive::Ssz;
@@ -6,11 +6,13 @@ use serde::{Deserialize, Serialize};
 use typenum::U4096;
 
 #[derive(Clone, Debug, PartialEq, Eq, Ssz, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct BlockBody {
     pub attestations: List<SignedVote, U4096>,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Ssz, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct BlockHeader {
     pub slot: Slot,
     pub proposer_index: ValidatorIndex,
@@ -20,6 +22,7 @@ pub struct BlockHeader {
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Ssz, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct Block {
     pub slot: Slot,
     pub proposer_index: ValidatorIndex,
@@ -29,10 +32,11 @@ pub struct Block {
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Ssz, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct SignedBlock {
     pub message: Block,
-    /// Placeholder for real signature type
-    pub signature: Bytes32,
+    /// Proposer's signature over `hash_tree_root(message)`, at `block_signing_epoch(message.slot)`.
+    pub signature: Signature,
 }
 
 // Helper function to compute hash tree root
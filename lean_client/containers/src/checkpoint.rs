@@ -2,7 +2,8 @@ use crate::{Bytes32, Slot};
 use ssz_derive::Ssz;
 use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Debug, PartialEq, Eq, Ssz, Default, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Ssz, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct Checkpoint {
     pub root: Bytes32,
     pub slot: Slot,
@@ -1,10 +1,19 @@
+use crate::{Slot, ValidatorIndex};
 use ssz_derive::Ssz;
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Debug, PartialEq, Eq, Ssz, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct Config {
     /// total validators in the network
     pub num_validators: u64,
     /// genesis timestamp (seconds since UNIX epoch)
     pub genesis_time: u64,
-}
\ No newline at end of file
+}
+
+impl Config {
+    /// Simple RR proposer rule (round-robin). Nobody proposes without validators.
+    pub fn is_proposer(&self, slot: Slot, index: ValidatorIndex) -> bool {
+        self.num_validators != 0 && (slot.0 % self.num_validators) == (index.0 % self.num_validators)
+    }
+}
@@ -4,7 +4,9 @@ pub mod slot;
 pub mod checkpoint;
 pub mod vote;
 pub mod block;
+pub mod signature;
 pub mod state;
+pub mod validator;
 
 #[cfg(test)]
 pub mod test_vectors;
@@ -12,9 +14,11 @@ pub mod test_vectors;
 pub use block::{Block, BlockBody, BlockHeader, SignedBlock};
 pub use checkpoint::Checkpoint;
 pub use config::Config as ContainerConfig;
+pub use signature::{PublicKey, Signature, SignatureScheme, Xmss};
 pub use slot::Slot;
-pub use state::State;
+pub use state::{State, StateTransitionError, VoteCheck};
 pub use types::{Bytes32, Uint64, ValidatorIndex};
+pub use validator::Validator;
 pub use vote::{SignedVote, Vote};
 // Re-export grandine ssz so tests can reference it if needed
 pub use ssz;
\ No newline at end of file
@@ -0,0 +1,261 @@
+//! Hash-based signatures: Winternitz one-time keys under a Merkle tree (XMSS).
+//!
+//! Every validator key covers `2^tree_height` epochs and must sign at most one message per epoch.
+//! Blocks and votes of the same slot get separate epochs, see [`block_signing_epoch`] and
+//! [`vote_signing_epoch`].
+use crate::{Bytes32, Slot};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use ssz::PersistentList as List;
+use ssz::H256;
+use ssz_derive::Ssz;
+use typenum::{Unsigned, U32, U67};
+
+/// Bits signed per Winternitz chain.
+pub const CHAIN_DIGIT_BITS: usize = 4;
+/// Hash steps from a chain's secret start to its public end.
+pub const CHAIN_LENGTH: usize = (1 << CHAIN_DIGIT_BITS) - 1;
+/// 64 chains for the 256-bit message digest plus 3 for its checksum.
+pub const NUM_CHAINS: usize = 67;
+const NUM_MESSAGE_CHAINS: usize = 64;
+pub const MAX_TREE_HEIGHT: usize = 32;
+/// One-time keys per slot: a proposer signs both its block and its vote.
+pub const EPOCHS_PER_SLOT: u64 = 2;
+
+pub type NumChains = U67;
+pub type MaxTreeHeight = U32;
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Ssz, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PublicKey {
+    /// Root of the Merkle tree over every epoch's one-time public key.
+    pub root: Bytes32,
+    /// Mixed into every hash so keys can't be attacked all at once.
+    pub public_seed: Bytes32,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Ssz, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Signature {
+    /// One intermediate value per Winternitz chain.
+    pub chains: List<Bytes32, NumChains>,
+    /// Siblings from the epoch's leaf up to the tree root.
+    pub auth_path: List<Bytes32, MaxTreeHeight>,
+}
+
+/// Reason a message could not be signed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SignatureError {
+    EpochOutOfRange { epoch: u64, lifetime: u64 },
+}
+
+impl std::fmt::Display for SignatureError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::EpochOutOfRange { epoch, lifetime } =>
+                write!(f, "Epoch {} is outside the key lifetime of {} epochs", epoch, lifetime),
+        }
+    }
+}
+
+impl std::error::Error for SignatureError {}
+
+/// Signs and verifies 32-byte message roots for a given epoch.
+pub trait SignatureScheme {
+    type SecretKey;
+
+    fn sign(&self, secret_key: &Self::SecretKey, epoch: u64, message: Bytes32) -> Result<Signature, SignatureError>;
+
+    fn verify(&self, public_key: &PublicKey, epoch: u64, message: Bytes32, signature: &Signature) -> bool;
+}
+
+pub fn block_signing_epoch(slot: Slot) -> u64 {
+    slot.0 * EPOCHS_PER_SLOT
+}
+
+pub fn vote_signing_epoch(slot: Slot) -> u64 {
+    slot.0 * EPOCHS_PER_SLOT + 1
+}
+
+/// XMSS over SHA-256 with Winternitz parameter w = 16.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Xmss;
+
+/// Seeds plus the cached Merkle tree, so signing doesn't rebuild every leaf.
+#[derive(Clone)]
+pub struct XmssSecretKey {
+    secret_seed: Bytes32,
+    public_seed: Bytes32,
+    /// `tree[0]` holds the leaves, the last level holds the root.
+    tree: Vec<Vec<Bytes32>>,
+}
+
+impl std::fmt::Debug for XmssSecretKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("XmssSecretKey").field("lifetime", &self.lifetime()).finish_non_exhaustive()
+    }
+}
+
+impl XmssSecretKey {
+    /// Number of epochs this key can sign for.
+    pub fn lifetime(&self) -> u64 {
+        self.tree[0].len() as u64
+    }
+
+    pub fn public_key(&self) -> PublicKey {
+        PublicKey { root: self.tree[self.tree.len() - 1][0], public_seed: self.public_seed }
+    }
+}
+
+impl Xmss {
+    /// Derives a key pair valid for `2^tree_height` epochs from `seed`.
+    /// Generation hashes every one-time key, so its cost doubles with each level.
+    pub fn generate_key(&self, seed: [u8; 32], tree_height: usize) -> (PublicKey, XmssSecretKey) {
+        assert!(tree_height <= MAX_TREE_HEIGHT, "tree height {} exceeds {}", tree_height, MAX_TREE_HEIGHT);
+        let secret_seed = hash(&[b"secret", &seed]);
+        let public_seed = hash(&[b"public", &seed]);
+
+        let leaves = (0..1u64 << tree_height)
+            .map(|epoch| {
+                let ends: Vec<Bytes32> = (0..NUM_CHAINS)
+                    .map(|i| walk_chain(&public_seed, epoch, i, chain_start(&secret_seed, epoch, i), 0, CHAIN_LENGTH))
+                    .collect();
+                leaf_hash(&public_seed, epoch, &ends)
+            })
+            .collect();
+
+        let mut tree: Vec<Vec<Bytes32>> = vec![leaves];
+        for level in 1..=tree_height {
+            let below = &tree[level - 1];
+            let nodes = below
+                .chunks(2)
+                .enumerate()
+                .map(|(index, pair)| node_hash(&public_seed, level, index as u64, &pair[0], &pair[1]))
+                .collect();
+            tree.push(nodes);
+        }
+
+        let secret_key = XmssSecretKey { secret_seed, public_seed, tree };
+        (secret_key.public_key(), secret_key)
+    }
+}
+
+impl SignatureScheme for Xmss {
+    type SecretKey = XmssSecretKey;
+
+    fn sign(&self, secret_key: &XmssSecretKey, epoch: u64, message: Bytes32) -> Result<Signature, SignatureError> {
+        if epoch >= secret_key.lifetime() {
+            return Err(SignatureError::EpochOutOfRange { epoch, lifetime: secret_key.lifetime() });
+        }
+        let XmssSecretKey { secret_seed, public_seed, tree } = secret_key;
+
+        let mut chains = List::default();
+        for (i, digit) in message_digits(public_seed, epoch, &message).into_iter().enumerate() {
+            let value = walk_chain(public_seed, epoch, i, chain_start(secret_seed, epoch, i), 0, digit);
+            chains.push(value).expect("NUM_CHAINS fits the chain list");
+        }
+
+        let mut auth_path = List::default();
+        for (level, nodes) in tree[..tree.len() - 1].iter().enumerate() {
+            let sibling = nodes[((epoch >> level) ^ 1) as usize];
+            auth_path.push(sibling).expect("tree height is at most MAX_TREE_HEIGHT");
+        }
+
+        Ok(Signature { chains, auth_path })
+    }
+
+    fn verify(&self, public_key: &PublicKey, epoch: u64, message: Bytes32, signature: &Signature) -> bool {
+        let chains = list_to_vec(&signature.chains);
+        let auth_path = list_to_vec(&signature.auth_path);
+        if chains.len() != NUM_CHAINS || epoch >> auth_path.len() != 0 {
+            return false;
+        }
+
+        let public_seed = &public_key.public_seed;
+        let ends: Vec<Bytes32> = message_digits(public_seed, epoch, &message)
+            .into_iter()
+            .enumerate()
+            .map(|(i, digit)| walk_chain(public_seed, epoch, i, chains[i], digit, CHAIN_LENGTH - digit))
+            .collect();
+
+        let mut node = leaf_hash(public_seed, epoch, &ends);
+        for (level, sibling) in auth_path.iter().enumerate() {
+            let index = epoch >> level;
+            node = if index & 1 == 0 {
+                node_hash(public_seed, level + 1, index >> 1, &node, sibling)
+            } else {
+                node_hash(public_seed, level + 1, index >> 1, sibling, &node)
+            };
+        }
+        node == public_key.root
+    }
+}
+
+fn hash(parts: &[&[u8]]) -> Bytes32 {
+    let mut hasher = Sha256::new();
+    for part in parts {
+        hasher.update(part);
+    }
+    Bytes32(H256(hasher.finalize().into()))
+}
+
+fn chain_start(secret_seed: &Bytes32, epoch: u64, chain: usize) -> Bytes32 {
+    hash(&[secret_seed.0.as_bytes(), &epoch.to_le_bytes(), &(chain as u64).to_le_bytes()])
+}
+
+/// Hashes `value`, which sits at `position` of its chain, `steps` times.
+/// Every step is tweaked with its position so values from different steps never collide.
+fn walk_chain(public_seed: &Bytes32, epoch: u64, chain: usize, mut value: Bytes32, position: usize, steps: usize) -> Bytes32 {
+    for step in position..position + steps {
+        value = hash(&[
+            public_seed.0.as_bytes(),
+            &epoch.to_le_bytes(),
+            &(chain as u64).to_le_bytes(),
+            &(step as u64).to_le_bytes(),
+            value.0.as_bytes(),
+        ]);
+    }
+    value
+}
+
+fn leaf_hash(public_seed: &Bytes32, epoch: u64, chain_ends: &[Bytes32]) -> Bytes32 {
+    let epoch_bytes = epoch.to_le_bytes();
+    let mut parts: Vec<&[u8]> = vec![public_seed.0.as_bytes(), b"leaf", &epoch_bytes];
+    parts.extend(chain_ends.iter().map(|end| end.0.as_bytes()));
+    hash(&parts)
+}
+
+fn node_hash(public_seed: &Bytes32, level: usize, index: u64, left: &Bytes32, right: &Bytes32) -> Bytes32 {
+    hash(&[
+        public_seed.0.as_bytes(),
+        b"node",
+        &(level as u64).to_le_bytes(),
+        &index.to_le_bytes(),
+        left.0.as_bytes(),
+        right.0.as_bytes(),
+    ])
+}
+
+/// Base-16 digits of the epoch-bound message digest, followed by their checksum.
+/// Raising a message digit lowers the checksum, so no forger can reuse a signature's chains.
+fn message_digits(public_seed: &Bytes32, epoch: u64, message: &Bytes32) -> Vec<usize> {
+    let digest = hash(&[public_seed.0.as_bytes(), b"message", &epoch.to_le_bytes(), message.0.as_bytes()]);
+    let mut digits: Vec<usize> = digest.0.as_bytes().iter().flat_map(|byte| [(byte >> 4) as usize, (byte & 0xf) as usize]).collect();
+    debug_assert_eq!(digits.len(), NUM_MESSAGE_CHAINS);
+
+    let checksum: usize = digits.iter().map(|digit| CHAIN_LENGTH - digit).sum();
+    digits.extend([(checksum >> 8) & 0xf, (checksum >> 4) & 0xf, checksum & 0xf]);
+    debug_assert_eq!(digits.len(), NUM_CHAINS);
+    digits
+}
+
+fn list_to_vec<N: Unsigned>(list: &List<Bytes32, N>) -> Vec<Bytes32> {
+    // PersistentList doesn't expose iter; probe sequentially until index error
+    let mut values = Vec::new();
+    let mut i: u64 = 0;
+    while let Ok(value) = list.get(i) {
+        values.push(*value);
+        i += 1;
+    }
+    values
+}
@@ -2,7 +2,7 @@ use ssz_derive::Ssz;
 use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Ssz, Default, Serialize, Deserialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Ssz, Default, Serialize, Deserialize)]
 pub struct Slot(pub u64);
 
 impl PartialOrd for Slot {
@@ -18,12 +18,13 @@ impl Ord for Slot {
 
 impl Slot {
     /// Port of 3SF-mini justifiability rule from the spec notes.
+    /// Slots before `finalized` are never justifiable.
     pub fn is_justifiable_after(self, finalized: Slot) -> bool {
-        assert!(self >= finalized, "candidate must not be before finalized");
-        let delta = self.0 - finalized.0;
-        // <=5 OR perfect square OR pronic (x^2 + x)
-        delta <= 5
-            || ((delta as f64).sqrt().fract() == 0.0)
-            || ((((delta as f64) + 0.25).sqrt().fract() - 0.5).abs() < 1e-12)
+        let Some(delta) = self.0.checked_sub(finalized.0) else {
+            return false;
+        };
+        // <=5 OR perfect square OR pronic (x^2 + x); both have floor(sqrt(delta)) as their x
+        let root = delta.isqrt();
+        delta <= 5 || root * root == delta || root * (root + 1) == delta
     }
 }
\ No newline at end of file
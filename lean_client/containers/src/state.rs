@@ -1,14 +1,85 @@
-use crate::{Bytes32, Checkpoint, ContainerConfig, Slot, Uint64, ValidatorIndex, block::{Block, BlockBody, BlockHeader, SignedBlock, hash_tree_root}, SignedVote};
-use ssz::PersistentList as List;
+use crate::{Bytes32, Checkpoint, ContainerConfig, Slot, Uint64, ValidatorIndex, block::{Block, BlockBody, BlockHeader, SignedBlock, hash_tree_root}, SignedVote, Vote};
+use crate::signature::{block_signing_epoch, vote_signing_epoch, PublicKey, SignatureScheme, Xmss};
+use crate::Validator;
+use ssz::{BitList, PersistentList as List};
 use ssz_derive::Ssz;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::collections::BTreeMap;
+use typenum::Unsigned;
 
 pub const VALIDATOR_REGISTRY_LIMIT: usize = 1 << 12;     // 4096
+pub const HISTORICAL_ROOTS_LIMIT: usize = 1 << 18;       // 262144
 pub const JUSTIFICATION_ROOTS_LIMIT: usize = 1 << 18;    // 262144
 pub const JUSTIFICATIONS_VALIDATORS_MAX: usize = VALIDATOR_REGISTRY_LIMIT * JUSTIFICATION_ROOTS_LIMIT;
 
+// Type-level versions of the limits above, as required by the SSZ collections.
+pub type HistoricalRootsLimit = typenum::U262144;
+pub type JustificationRootsLimit = typenum::U262144;
+pub type JustificationsValidatorsMax = typenum::U1073741824;
+pub type ValidatorRegistryLimit = typenum::U4096;
+
+pub type HistoricalBlockHashes = List<Bytes32, HistoricalRootsLimit>;
+pub type JustifiedSlots = BitList<HistoricalRootsLimit>;
+pub type JustificationsRoots = List<Bytes32, JustificationRootsLimit>;
+pub type JustificationsValidators = BitList<JustificationsValidatorsMax>;
+pub type Validators = List<Validator, ValidatorRegistryLimit>;
+
+/// Reason a block (or slot advance) was rejected by the state transition.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StateTransitionError {
+    SlotMismatch { block_slot: Slot, state_slot: Slot },
+    BlockOlderThanLatestHeader { block_slot: Slot, header_slot: Slot },
+    IncorrectProposer { slot: Slot, proposer_index: ValidatorIndex },
+    ParentRootMismatch { expected: Bytes32, actual: Bytes32 },
+    StateRootMismatch { expected: Bytes32, actual: Bytes32 },
+    UnknownValidator(ValidatorIndex),
+    InvalidBlockSignature { proposer_index: ValidatorIndex, slot: Slot },
+    InvalidVoteSignature { validator_id: ValidatorIndex, slot: Slot },
+    TargetSlotNotInFuture { current: Slot, target: Slot },
+    RootsLimitExceeded { len: usize, limit: usize },
+    JustificationVotesLength { root: Bytes32, len: usize },
+    JustificationValidatorsLength { roots: usize, len: usize },
+}
+
+impl std::fmt::Display for StateTransitionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::SlotMismatch { block_slot, state_slot } =>
+                write!(f, "Block slot mismatch: block slot {}, state slot {}", block_slot.0, state_slot.0),
+            Self::BlockOlderThanLatestHeader { block_slot, header_slot } =>
+                write!(f, "Block is older than latest header: block slot {}, header slot {}", block_slot.0, header_slot.0),
+            Self::IncorrectProposer { slot, proposer_index } =>
+                write!(f, "Incorrect block proposer: validator {} for slot {}", proposer_index.0, slot.0),
+            Self::ParentRootMismatch { expected, actual } =>
+                write!(f, "Block parent root mismatch: expected {:?}, got {:?}", expected.0, actual.0),
+            Self::StateRootMismatch { expected, actual } =>
+                write!(f, "Invalid block state root: block claims {:?}, computed {:?}", expected.0, actual.0),
+            Self::UnknownValidator(index) => write!(f, "No public key registered for validator {}", index.0),
+            Self::InvalidBlockSignature { proposer_index, slot } =>
+                write!(f, "Invalid block signature from proposer {} for slot {}", proposer_index.0, slot.0),
+            Self::InvalidVoteSignature { validator_id, slot } =>
+                write!(f, "Invalid vote signature from validator {} for slot {}", validator_id.0, slot.0),
+            Self::TargetSlotNotInFuture { current, target } =>
+                write!(f, "Target slot must be in the future: current {}, target {}", current.0, target.0),
+            Self::RootsLimitExceeded { len, limit } => write!(f, "{} roots exceed the list limit {}", len, limit),
+            Self::JustificationVotesLength { root, len } => write!(
+                f,
+                "Justification votes for {:?} have length {}, expected {}",
+                root.0, len, VALIDATOR_REGISTRY_LIMIT
+            ),
+            Self::JustificationValidatorsLength { roots, len } => write!(
+                f,
+                "{} justification vote bits do not match {} pending roots of {} validators each",
+                len, roots, VALIDATOR_REGISTRY_LIMIT
+            ),
+        }
+    }
+}
+
+impl std::error::Error for StateTransitionError {}
+
 #[derive(Clone, Debug, PartialEq, Eq, Ssz, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct State {
     // --- configuration (spec-local) ---
     pub config: ContainerConfig,
@@ -22,25 +93,82 @@ pub struct State {
     pub latest_finalized: Checkpoint,
 
     // --- historical data ---
-    #[ssz(skip)]
-    #[serde(skip)]
-    pub historical_block_hashes: Vec<Bytes32>,
+    pub historical_block_hashes: HistoricalBlockHashes,
 
     // --- flattened justification tracking ---
-    #[ssz(skip)]
-    #[serde(skip)]
-    pub justified_slots: Vec<bool>,
-    #[ssz(skip)]
-    #[serde(skip)]
-    pub justifications_roots: Vec<Bytes32>,
-    // Flattened votes vector. Enforced by logic/tests to not exceed limits.
-    #[ssz(skip)]
-    #[serde(skip)]
-    pub justifications_validators: Vec<bool>,
+    #[serde(with = "bitlist_as_bools")]
+    pub justified_slots: JustifiedSlots,
+
+    // --- validator registry, indexed by `ValidatorIndex` ---
+    pub validators: Validators,
+
+    // --- pending justifications ---
+    pub justifications_roots: JustificationsRoots,
+    // Flattened votes vector, `VALIDATOR_REGISTRY_LIMIT` bits per entry of `justifications_roots`.
+    #[serde(with = "bitlist_as_bools")]
+    pub justifications_validators: JustificationsValidators,
+}
+
+/// Copies the roots out of an SSZ list so they can be indexed and extended.
+pub fn roots_to_vec<N: Unsigned>(list: &List<Bytes32, N>) -> Vec<Bytes32> {
+    // PersistentList doesn't expose iter; probe sequentially until index error
+    let mut roots = Vec::new();
+    let mut i: u64 = 0;
+    while let Ok(root) = list.get(i) {
+        roots.push(*root);
+        i += 1;
+    }
+    roots
+}
+
+/// Builds a root list of any limit, e.g. `HistoricalBlockHashes` or `JustificationsRoots`.
+pub fn roots_from_slice<N: Unsigned>(roots: &[Bytes32]) -> Result<List<Bytes32, N>, StateTransitionError> {
+    let mut list = List::default();
+    for root in roots {
+        list.push(*root).map_err(|_| StateTransitionError::RootsLimitExceeded { len: roots.len(), limit: N::USIZE })?;
+    }
+    Ok(list)
+}
+
+pub fn bits_to_vec<N: Unsigned>(bits: &BitList<N>) -> Vec<bool> {
+    bits.iter().by_vals().collect()
+}
+
+pub fn bits_from_slice<N: Unsigned>(values: &[bool]) -> BitList<N> {
+    let mut bits = BitList::with_length(values.len());
+    for (i, value) in values.iter().enumerate() {
+        bits.set(i, *value);
+    }
+    bits
+}
+
+/// Serializes bitlists as plain boolean sequences so fixtures stay human readable.
+mod bitlist_as_bools {
+    use super::*;
+
+    pub fn serialize<N: Unsigned, S: Serializer>(bits: &BitList<N>, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(bits.iter().by_vals())
+    }
+
+    pub fn deserialize<'de, N: Unsigned, D: Deserializer<'de>>(deserializer: D) -> Result<BitList<N>, D::Error> {
+        let values = Vec::<bool>::deserialize(deserializer)?;
+        if values.len() > N::USIZE {
+            return Err(serde::de::Error::invalid_length(values.len(), &"a bitlist within its SSZ limit"));
+        }
+        Ok(bits_from_slice(&values))
+    }
 }
 
 impl State {
-    pub fn generate_genesis(genesis_time: Uint64, num_validators: Uint64) -> Self {
+    /// Genesis state with one validator per pubkey, indexed in the given order.
+    pub fn generate_genesis(genesis_time: Uint64, pubkeys: &[PublicKey]) -> Self {
+        let mut validators = List::default();
+        for pubkey in pubkeys {
+            validators
+                .push(Validator { pubkey: pubkey.clone() })
+                .expect("validator count must not exceed VALIDATOR_REGISTRY_LIMIT");
+        }
+
     let body_for_root = BlockBody { attestations: Default::default() };
         let header = BlockHeader {
             slot: Slot(0),
@@ -50,76 +178,140 @@ impl State {
             body_root: hash_tree_root(&body_for_root),
         };
         Self {
-            config: ContainerConfig { genesis_time: genesis_time.0, num_validators: num_validators.0 },
+            config: ContainerConfig { genesis_time: genesis_time.0, num_validators: pubkeys.len() as u64 },
             slot: Slot(0),
             latest_block_header: header,
+            // Zero until the first block; consumers start from `anchor_checkpoint` instead.
             latest_justified: Checkpoint { root: Bytes32(ssz::H256::zero()), slot: Slot(0) },
             latest_finalized: Checkpoint { root: Bytes32(ssz::H256::zero()), slot: Slot(0) },
-            historical_block_hashes: Vec::new(),
-            justified_slots: Vec::new(),
-            justifications_roots: Vec::new(),
-            justifications_validators: Vec::new(),
+            historical_block_hashes: List::default(),
+            justified_slots: BitList::default(),
+            validators,
+            justifications_roots: List::default(),
+            justifications_validators: BitList::default(),
+        }
+    }
+
+    /// Block the genesis state is anchored at: the latest header's block, committing to this state.
+    /// Only meaningful on the state returned by `generate_genesis`, whose header has an empty body.
+    pub fn genesis_block(&self) -> Block {
+        Block {
+            slot: self.latest_block_header.slot,
+            proposer_index: self.latest_block_header.proposer_index,
+            parent_root: self.latest_block_header.parent_root,
+            state_root: hash_tree_root(self),
+            body: BlockBody { attestations: Default::default() },
+        }
+    }
+
+    /// Checkpoint of the block this state was last advanced by, e.g. the genesis block at genesis.
+    /// A state can't contain its own root, so before `process_slot` caches it the root is filled in here.
+    pub fn anchor_checkpoint(&self) -> Checkpoint {
+        let mut header = self.latest_block_header.clone();
+        if header.state_root == Bytes32(ssz::H256::zero()) {
+            header.state_root = hash_tree_root(self);
         }
+        Checkpoint { root: hash_tree_root(&header), slot: header.slot }
     }
 
-    /// Simple RR proposer rule (round-robin).
+    /// Whether `index` proposes at the state's slot, see `ContainerConfig::is_proposer`.
     pub fn is_proposer(&self, index: ValidatorIndex) -> bool {
-        (self.slot.0 % self.config.num_validators) == (index.0 % self.config.num_validators)
+        self.config.is_proposer(self.slot, index)
     }
 
-    pub fn get_justifications(&self) -> BTreeMap<Bytes32, Vec<bool>> {
+    /// Pending votes per root. A decoded state may carry any number of vote bits, so they are
+    /// checked against the roots before being chunked.
+    pub fn get_justifications(&self) -> Result<BTreeMap<Bytes32, Vec<bool>>, StateTransitionError> {
         // Chunk validator votes per root using the fixed registry limit
         let limit = VALIDATOR_REGISTRY_LIMIT;
-    self.justifications_roots
-            .iter()
-            .enumerate()
-            .map(|(i, root)| {
-                let start = i * limit;
-                let end = start + limit;
-        (*root, self.justifications_validators[start..end].to_vec())
-            })
-            .collect()
-    }
-
-    pub fn with_justifications(mut self, mut map: BTreeMap<Bytes32, Vec<bool>>) -> Self {
-        // Expect each root to have exactly `VALIDATOR_REGISTRY_LIMIT` votes
+        let roots = roots_to_vec(&self.justifications_roots);
+        let validators = bits_to_vec(&self.justifications_validators);
+        if validators.len() != roots.len() * limit {
+            return Err(StateTransitionError::JustificationValidatorsLength { roots: roots.len(), len: validators.len() });
+        }
+        Ok(roots.into_iter().zip(validators.chunks(limit)).map(|(root, votes)| (root, votes.to_vec())).collect())
+    }
+
+    pub fn with_justifications(mut self, map: BTreeMap<Bytes32, Vec<bool>>) -> Result<Self, StateTransitionError> {
+        // Each root must have exactly `VALIDATOR_REGISTRY_LIMIT` votes; the map iterates roots sorted
         let limit = VALIDATOR_REGISTRY_LIMIT;
-        let mut roots: Vec<_> = map.keys().cloned().collect();
-        roots.sort();
-
-        let mut flat = Vec::with_capacity(roots.len() * limit);
-        for r in &roots {
-            let v = map.remove(r).expect("root present");
-            assert_eq!(v.len(), limit, "vote vector must match validator limit");
-            flat.extend_from_slice(&v);
+        let mut roots = Vec::with_capacity(map.len());
+        let mut flat = Vec::with_capacity(map.len() * limit);
+        for (root, votes) in map {
+            if votes.len() != limit {
+                return Err(StateTransitionError::JustificationVotesLength { root, len: votes.len() });
+            }
+            roots.push(root);
+            flat.extend_from_slice(&votes);
         }
 
-    self.justifications_roots = roots;
-    self.justifications_validators = flat;
-        self
+        self.justifications_roots = roots_from_slice(&roots)?;
+        self.justifications_validators = bits_from_slice(&flat);
+        Ok(self)
+    }
+
+    pub fn with_historical_hashes(mut self, hashes: Vec<Bytes32>) -> Result<Self, StateTransitionError> {
+        self.historical_block_hashes = roots_from_slice(&hashes)?;
+        Ok(self)
+    }
+
+    pub fn validator(&self, index: ValidatorIndex) -> Result<&Validator, StateTransitionError> {
+        self.validators.get(index.0).map_err(|_| StateTransitionError::UnknownValidator(index))
     }
 
-    pub fn with_historical_hashes(mut self, hashes: Vec<Bytes32>) -> Self {
-    self.historical_block_hashes = hashes;
-        self
+    pub fn validator_pubkey(&self, index: ValidatorIndex) -> Result<&PublicKey, StateTransitionError> {
+        self.validator(index).map(|validator| &validator.pubkey)
     }
 
-    pub fn state_transition(&self, signed_block: SignedBlock, valid_signatures: bool) -> Self {
-        assert!(valid_signatures, "Block signatures must be valid");
+    /// Checks the proposer's signature and that of every vote in the block body.
+    pub fn verify_block_signatures<S: SignatureScheme>(&self, scheme: &S, signed_block: &SignedBlock) -> Result<(), StateTransitionError> {
+        let block = &signed_block.message;
+        let pubkey = self.validator_pubkey(block.proposer_index)?;
+        if !scheme.verify(pubkey, block_signing_epoch(block.slot), hash_tree_root(block), &signed_block.signature) {
+            return Err(StateTransitionError::InvalidBlockSignature { proposer_index: block.proposer_index, slot: block.slot });
+        }
+
+        let mut i: u64 = 0;
+        while let Ok(signed_vote) = block.body.attestations.get(i) {
+            self.verify_vote_signature(scheme, signed_vote)?;
+            i += 1;
+        }
+        Ok(())
+    }
+
+    pub fn verify_vote_signature<S: SignatureScheme>(&self, scheme: &S, signed_vote: &SignedVote) -> Result<(), StateTransitionError> {
+        let vote = &signed_vote.data;
+        let validator_id = ValidatorIndex(vote.validator_id.0);
+        let pubkey = self.validator_pubkey(validator_id)?;
+        if !scheme.verify(pubkey, vote_signing_epoch(vote.slot), hash_tree_root(vote), &signed_vote.signature) {
+            return Err(StateTransitionError::InvalidVoteSignature { validator_id, slot: vote.slot });
+        }
+        Ok(())
+    }
+
+    /// Verifies the block's signatures with [`Xmss`], then applies it.
+    pub fn state_transition(&self, signed_block: SignedBlock) -> Result<Self, StateTransitionError> {
+        self.state_transition_with(&Xmss, signed_block)
+    }
+
+    pub fn state_transition_with<S: SignatureScheme>(&self, scheme: &S, signed_block: SignedBlock) -> Result<Self, StateTransitionError> {
+        self.verify_block_signatures(scheme, &signed_block)?;
 
         let block = signed_block.message;
-        let mut state = self.process_slots(block.slot);
-        state = state.process_block(&block);
+        let state = self.process_slots(block.slot)?.process_block(&block)?;
 
-    let state_for_hash = state.clone();
-    let state_root = hash_tree_root(&state_for_hash);
-        assert!(block.state_root == state_root, "Invalid block state root");
+        let state_root = hash_tree_root(&state);
+        if block.state_root != state_root {
+            return Err(StateTransitionError::StateRootMismatch { expected: block.state_root, actual: state_root });
+        }
 
-        state
+        Ok(state)
     }
 
-    pub fn process_slots(&self, target_slot: Slot) -> Self {
-        assert!(self.slot < target_slot, "Target slot must be in the future");
+    pub fn process_slots(&self, target_slot: Slot) -> Result<Self, StateTransitionError> {
+        if self.slot >= target_slot {
+            return Err(StateTransitionError::TargetSlotNotInFuture { current: self.slot, target: target_slot });
+        }
 
         let mut state = self.clone();
 
@@ -128,7 +320,7 @@ impl State {
             state.slot = Slot(state.slot.0 + 1);
         }
 
-        state
+        Ok(state)
     }
 
     pub fn process_slot(&self) -> Self {
@@ -147,25 +339,34 @@ impl State {
         self.clone()
     }
 
-    pub fn process_block(&self, block: &Block) -> Self {
-        let state = self.process_block_header(block);
+    pub fn process_block(&self, block: &Block) -> Result<Self, StateTransitionError> {
+        let state = self.process_block_header(block)?;
         state.process_operations(&block.body)
     }
 
-    pub fn process_block_header(&self, block: &Block) -> Self {
-        if !(block.slot == self.slot) { std::panic::panic_any(String::from("Block slot mismatch")); }
-        if !(block.slot > self.latest_block_header.slot) { std::panic::panic_any(String::from("Block is older than latest header")); }
-        if !self.is_proposer(block.proposer_index) { std::panic::panic_any(String::from("Incorrect block proposer")); }
+    pub fn process_block_header(&self, block: &Block) -> Result<Self, StateTransitionError> {
+        if block.slot != self.slot {
+            return Err(StateTransitionError::SlotMismatch { block_slot: block.slot, state_slot: self.slot });
+        }
+        if block.slot <= self.latest_block_header.slot {
+            return Err(StateTransitionError::BlockOlderThanLatestHeader {
+                block_slot: block.slot,
+                header_slot: self.latest_block_header.slot,
+            });
+        }
+        if !self.is_proposer(block.proposer_index) {
+            return Err(StateTransitionError::IncorrectProposer { slot: block.slot, proposer_index: block.proposer_index });
+        }
 
-        // Create a mutable clone for hash computation
-    let latest_header_for_hash = self.latest_block_header.clone();
-    let parent_root = hash_tree_root(&latest_header_for_hash);
-    if block.parent_root != parent_root { std::panic::panic_any(String::from("Block parent root mismatch")); }
+        let parent_root = hash_tree_root(&self.latest_block_header);
+        if block.parent_root != parent_root {
+            return Err(StateTransitionError::ParentRootMismatch { expected: parent_root, actual: block.parent_root });
+        }
 
-    let mut new_historical_hashes = self.historical_block_hashes.clone();
+        let mut new_historical_hashes = roots_to_vec(&self.historical_block_hashes);
         new_historical_hashes.push(parent_root);
 
-    let mut new_justified_slots = self.justified_slots.clone();
+        let mut new_justified_slots = bits_to_vec(&self.justified_slots);
         new_justified_slots.push(self.latest_block_header.slot == Slot(0));
 
         let num_empty_slots = (block.slot.0 - self.latest_block_header.slot.0 - 1) as usize;
@@ -185,46 +386,63 @@ impl State {
             state_root: Bytes32(ssz::H256::zero()),
         };
 
-        let mut new_latest_justified = self.latest_justified.clone();
-        let mut new_latest_finalized = self.latest_finalized.clone();
-
-        if self.latest_block_header.slot == Slot(0) {
-            new_latest_justified.root = parent_root;
-            new_latest_finalized.root = parent_root;
-        }
+        // The genesis state can't commit to the genesis block root, so its checkpoints only
+        // point at the genesis anchor, the parent here, once the first block builds on it.
+        let (new_latest_justified, new_latest_finalized) = if self.latest_block_header.slot == Slot(0) {
+            let anchor = Checkpoint { root: parent_root, slot: Slot(0) };
+            (anchor.clone(), anchor)
+        } else {
+            (self.latest_justified.clone(), self.latest_finalized.clone())
+        };
 
-        Self {
+        Ok(Self {
             config: self.config.clone(),
             slot: self.slot,
             latest_block_header: new_latest_block_header,
             latest_justified: new_latest_justified,
             latest_finalized: new_latest_finalized,
-            historical_block_hashes: new_historical_hashes,
-            justified_slots: new_justified_slots,
+            historical_block_hashes: roots_from_slice(&new_historical_hashes)?,
+            justified_slots: bits_from_slice(&new_justified_slots),
+            validators: self.validators.clone(),
             justifications_roots: self.justifications_roots.clone(),
             justifications_validators: self.justifications_validators.clone(),
+        })
+    }
+
+    /// Whether `process_attestations` would count `vote` towards its target in this state.
+    /// Use `vote_check` instead when checking many votes.
+    pub fn is_valid_vote(&self, vote: &Vote) -> bool {
+        self.vote_check().is_valid(vote)
+    }
+
+    /// Copies out what `is_valid_vote` needs, so it is built and hashed once for many votes.
+    pub fn vote_check(&self) -> VoteCheck {
+        VoteCheck {
+            justified_slots: bits_to_vec(&self.justified_slots),
+            historical_block_hashes: roots_to_vec(&self.historical_block_hashes),
+            latest_header: header_checkpoint(&self.latest_block_header),
+            finalized_slot: self.latest_finalized.slot,
         }
     }
 
-    pub fn process_operations(&self, body: &BlockBody) -> Self {
+    pub fn process_operations(&self, body: &BlockBody) -> Result<Self, StateTransitionError> {
         self.process_attestations(&body.attestations)
     }
 
-    pub fn process_attestations(&self, attestations: &List<SignedVote, typenum::U4096>) -> Self {
-        let mut justifications = self.get_justifications();
+    pub fn process_attestations(&self, attestations: &List<SignedVote, typenum::U4096>) -> Result<Self, StateTransitionError> {
+        let mut justifications = self.get_justifications()?;
         let mut latest_justified = self.latest_justified.clone();
         let mut latest_finalized = self.latest_finalized.clone();
-        let mut justified_slots = self.justified_slots.clone();
+        let mut justified_slots = bits_to_vec(&self.justified_slots);
+        let historical_block_hashes = roots_to_vec(&self.historical_block_hashes);
+        let latest_header = header_checkpoint(&self.latest_block_header);
 
         // PersistentList doesn't expose iter; convert to Vec for simple iteration for now
         // Build a temporary Vec by probing sequentially until index error
         let mut votes_vec: Vec<SignedVote> = Vec::new();
         let mut i: u64 = 0;
-        loop {
-            match attestations.get(i) {
-                Ok(v) => votes_vec.push(v.clone()),
-                Err(_) => break,
-            }
+        while let Ok(v) = attestations.get(i) {
+            votes_vec.push(v.clone());
             i += 1;
         }
 
@@ -233,92 +451,134 @@ impl State {
                 let target_slot = vote.target.slot;
                 let source_slot = vote.source.slot;
                 let target_root = vote.target.root;
-                let source_root = vote.source.root;
 
                 let target_slot_int = target_slot.0 as usize;
                 let source_slot_int = source_slot.0 as usize;
 
-                let source_is_justified = justified_slots.get(source_slot_int).copied().unwrap_or(false);
-                let target_already_justified = justified_slots.get(target_slot_int).copied().unwrap_or(false);
-
-                let source_root_matches_history = self.historical_block_hashes.get(source_slot_int)
-                    .map(|&root| root == source_root)
-                    .unwrap_or(false);
-
-                let target_root_matches_history = self.historical_block_hashes.get(target_slot_int)
-                    .map(|&root| root == target_root)
-                    .unwrap_or(false);
-
-                let latest_header_for_hash = self.latest_block_header.clone();
-                let target_matches_latest_header = target_slot == self.latest_block_header.slot &&
-                    target_root == hash_tree_root(&latest_header_for_hash);
-
-                let target_root_is_valid = target_root_matches_history || target_matches_latest_header;
-                let target_is_after_source = target_slot > source_slot;
-                let target_is_justifiable = target_slot.is_justifiable_after(latest_finalized.slot);
-
-                let is_valid_vote = source_is_justified &&
-                    !target_already_justified &&
-                    source_root_matches_history &&
-                    target_root_is_valid &&
-                    target_is_after_source &&
-                    target_is_justifiable;
+                let is_valid_vote = vote_is_valid(
+                    &vote,
+                    &justified_slots,
+                    &historical_block_hashes,
+                    &latest_header,
+                    latest_finalized.slot,
+                );
 
                 if !is_valid_vote { continue; }
 
-                if !justifications.contains_key(&target_root) {
-                    let limit = VALIDATOR_REGISTRY_LIMIT;
-                    justifications.insert(target_root, vec![false; limit]);
-                }
-
                 let validator_id = vote.validator_id.0 as usize;
-                if let Some(votes) = justifications.get_mut(&target_root) {
-                    if validator_id < votes.len() && !votes[validator_id] {
-                        votes[validator_id] = true;
-
-                        let count = votes.iter().filter(|&&v| v).count();
-                        if 3 * count >= 2 * self.config.num_validators as usize {
-                            latest_justified = vote.target;
+                let votes = justifications.entry(target_root).or_insert_with(|| vec![false; VALIDATOR_REGISTRY_LIMIT]);
+                if validator_id < votes.len() && !votes[validator_id] {
+                    votes[validator_id] = true;
 
-                            while justified_slots.len() <= target_slot_int {
-                                justified_slots.push(false);
-                            }
-                            justified_slots[target_slot_int] = true;
-
-                            justifications.remove(&target_root);
+                    let count = votes.iter().filter(|&&v| v).count();
+                    if 3 * count >= 2 * self.config.num_validators as usize {
+                        latest_justified = vote.target;
 
-                            let mut is_finalizable = true;
-                            for s in (source_slot_int + 1)..target_slot_int {
-                                if Slot(s as u64).is_justifiable_after(latest_finalized.slot) {
-                                    is_finalizable = false;
-                                    break;
-                                }
+                        while justified_slots.len() <= target_slot_int {
+                            justified_slots.push(false);
+                        }
+                        justified_slots[target_slot_int] = true;
+
+                        justifications.remove(&target_root);
+
+                        // Slots at or before the finalized one can't be justified, so they never
+                        // stand between the source and the target
+                        let first_open_slot = (source_slot_int + 1).max(latest_finalized.slot.0 as usize + 1);
+                        let mut is_finalizable = true;
+                        for s in first_open_slot..target_slot_int {
+                            if Slot(s as u64).is_justifiable_after(latest_finalized.slot) {
+                                is_finalizable = false;
+                                break;
                             }
+                        }
 
-                            if is_finalizable {
-                                latest_finalized = vote.source;
-                            }
+                        if is_finalizable {
+                            latest_finalized = vote.source;
                         }
                     }
                 }
             }
 
-    let mut new_state = self.clone().with_justifications(justifications);
+    let mut new_state = self.clone().with_justifications(justifications)?;
 
         new_state.latest_justified = latest_justified;
         new_state.latest_finalized = latest_finalized;
-    new_state.justified_slots = justified_slots;
+        new_state.justified_slots = bits_from_slice(&justified_slots);
+
+        Ok(new_state)
+    }
+}
+
+/// A state's vote validity rules, detached from the state; see `State::vote_check`.
+#[derive(Clone, Debug)]
+pub struct VoteCheck {
+    justified_slots: Vec<bool>,
+    historical_block_hashes: Vec<Bytes32>,
+    latest_header: Checkpoint,
+    finalized_slot: Slot,
+}
 
-        new_state
+impl VoteCheck {
+    /// Same answer as `State::is_valid_vote` on the state this was built from.
+    pub fn is_valid(&self, vote: &Vote) -> bool {
+        vote_is_valid(
+            vote,
+            &self.justified_slots,
+            &self.historical_block_hashes,
+            &self.latest_header,
+            self.finalized_slot,
+        )
     }
 }
 
+fn header_checkpoint(header: &BlockHeader) -> Checkpoint {
+    Checkpoint { root: hash_tree_root(header), slot: header.slot }
+}
+
+/// A vote counts if its justified source and its target are both on this chain and the
+/// target is a later, not yet justified, slot that may still be justified.
+fn vote_is_valid(
+    vote: &Vote,
+    justified_slots: &[bool],
+    historical_block_hashes: &[Bytes32],
+    latest_header: &Checkpoint,
+    finalized_slot: Slot,
+) -> bool {
+    let target_slot_int = vote.target.slot.0 as usize;
+    let source_slot_int = vote.source.slot.0 as usize;
+
+    let source_is_justified = justified_slots.get(source_slot_int).copied().unwrap_or(false);
+    let target_already_justified = justified_slots.get(target_slot_int).copied().unwrap_or(false);
+
+    let source_root_matches_history = historical_block_hashes.get(source_slot_int)
+        .map(|&root| root == vote.source.root)
+        .unwrap_or(false);
+
+    let target_root_matches_history = historical_block_hashes.get(target_slot_int)
+        .map(|&root| root == vote.target.root)
+        .unwrap_or(false);
+
+    let target_matches_latest_header = vote.target == *latest_header;
+
+    let target_root_is_valid = target_root_matches_history || target_matches_latest_header;
+    let target_is_after_source = vote.target.slot > vote.source.slot;
+    // Targets before the finalized slot can never be justified
+    let target_is_justifiable = vote.target.slot >= finalized_slot && vote.target.slot.is_justifiable_after(finalized_slot);
+
+    source_is_justified &&
+        !target_already_justified &&
+        source_root_matches_history &&
+        target_root_is_valid &&
+        target_is_after_source &&
+        target_is_justifiable
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     #[test]
     fn proposer_round_robin() {
-        let st = State::generate_genesis(Uint64(0), Uint64(4));
+        let st = State::generate_genesis(Uint64(0), &vec![PublicKey::default(); 4]);
         assert!(State { config: st.config.clone(), ..st.clone() }.is_proposer(ValidatorIndex(0)));
     }
 
@@ -347,14 +607,24 @@ mod tests {
 
     #[test]
     fn test_process_slots() {
-        let genesis_state = State::generate_genesis(Uint64(0), Uint64(10));
+        let genesis_state = State::generate_genesis(Uint64(0), &vec![PublicKey::default(); 10]);
         let target_slot = Slot(5);
 
-        let new_state = genesis_state.process_slots(target_slot);
+        let new_state = genesis_state.process_slots(target_slot).unwrap();
 
         assert_eq!(new_state.slot, target_slot);
     let genesis_state_for_hash = genesis_state.clone(); //this is sooooo bad
     assert_eq!(new_state.latest_block_header.state_root, hash_tree_root(&genesis_state_for_hash));
     }
 
+    #[test]
+    fn test_process_slots_rejects_non_future_target() {
+        let state = State::generate_genesis(Uint64(0), &vec![PublicKey::default(); 10]).process_slots(Slot(3)).unwrap();
+
+        assert_eq!(
+            state.process_slots(Slot(3)),
+            Err(StateTransitionError::TargetSlotNotInFuture { current: Slot(3), target: Slot(3) })
+        );
+    }
+
 }
\ No newline at end of file
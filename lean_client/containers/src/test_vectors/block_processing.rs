@@ -7,7 +7,7 @@ mod tests {
         let test_path = "test_vectors/block_processing/basic.yaml";
         if std::path::Path::new(test_path).exists() {
             TestRunner::run_block_processing_tests(test_path)
-                .expect("Block processing tests should pass");
+                .unwrap_or_else(|e| panic!("Block processing tests should pass: {}", e));
         } else {
             println!("Test vector file not found, skipping: {}", test_path);
         }
@@ -6,7 +6,9 @@ pub mod vote_processing;
 use serde::{Deserialize, Serialize};
 use crate::*;
 
+// Unknown keys are rejected at every level so a typo in a fixture can never be silently dropped.
 #[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct TestCase<T> {
     pub description: String,
     pub pre: T,
@@ -14,9 +16,13 @@ pub struct TestCase<T> {
     pub blocks: Option<Vec<SignedBlock>>,
     pub votes: Option<Vec<SignedVote>>,
     pub valid: bool,
+    /// `StateTransitionError` variant an invalid case must be rejected with, e.g. `IncorrectProposer`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expected_error: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct TestVector<T> {
     pub test_cases: Vec<TestCase<T>>,
     pub config: ContainerConfig,
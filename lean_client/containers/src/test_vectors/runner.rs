@@ -1,167 +1,356 @@
 use super::*;
+use crate::block::hash_tree_root;
+use crate::state::{bits_to_vec, roots_to_vec};
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::Path;
+use typenum::Unsigned;
 
 pub struct TestRunner;
 
+/// Which optional inputs of a [`TestCase`] a runner actually consumes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CaseInputs {
+    Blocks,
+    Votes,
+}
+
 impl TestRunner {
-    pub fn run_state_transition_tests<P: AsRef<Path>>(path: P) -> Result<(), Box<dyn std::error::Error>> {
+    /// Parses a YAML vector and rejects anything the runner would otherwise ignore:
+    /// unknown keys, inputs the runner does not consume, a post-state or a missing
+    /// `expected_error` on an invalid case, or a pre-state whose config disagrees with
+    /// the vector's config.
+    pub fn load_test_vector<P: AsRef<Path>>(path: P, inputs: CaseInputs) -> Result<TestVector<State>, Box<dyn std::error::Error>> {
+        let path = path.as_ref();
         let yaml_content = fs::read_to_string(path)?;
-        let test_vector: TestVector<State> = serde_yaml::from_str(&yaml_content)?;
-        
+        Self::parse_test_vector(&yaml_content, &path.display().to_string(), inputs)
+    }
+
+    pub fn parse_test_vector(yaml_content: &str, origin: &str, inputs: CaseInputs) -> Result<TestVector<State>, Box<dyn std::error::Error>> {
+        let test_vector: TestVector<State> = serde_yaml::from_str(yaml_content)
+            .map_err(|e| format!("{}: {}", origin, e))?;
+
         for (i, test_case) in test_vector.test_cases.iter().enumerate() {
-            println!("Running test case {}: {}", i, test_case.description);
-            
-            if let Some(ref blocks) = test_case.blocks {
-                let mut state = test_case.pre.clone();
-                let mut test_passed = true;
-                
-                for block in blocks {
-                    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
-                        state.state_transition(block.clone(), true)
-                    }));
-                    
-                    match result {
-                        Ok(new_state) => {
-                            if test_case.valid {
-                                state = new_state;
-                            } else {
-                                println!("  FAIL: Expected invalid block to be rejected");
-                                test_passed = false;
-                                break;
-                            }
-                        },
-                        Err(_) => {
-                            if !test_case.valid {
-                                println!("  PASS: Invalid block correctly rejected");
-                                continue;
-                            } else {
-                                println!("  FAIL: Valid block was rejected");
-                                test_passed = false;
-                                break;
-                            }
-                        }
-                    }
+            let case = format!("{}: test case {} ({})", origin, i, test_case.description);
+
+            if test_case.pre.config != test_vector.config {
+                return Err(format!("{}: pre-state config does not match the vector config", case).into());
+            }
+            if test_case.valid != test_case.expected_error.is_none() {
+                return Err(format!("{}: `expected_error` is required for invalid cases and only allowed there", case).into());
+            }
+            if let Some(ref post) = test_case.post {
+                if !test_case.valid {
+                    return Err(format!("{}: `post` is ignored for invalid cases", case).into());
                 }
-                
-                if test_passed && test_case.valid {
-                    if let Some(ref expected_post) = test_case.post {
-                        if state.slot == expected_post.slot && 
-                           state.latest_justified == expected_post.latest_justified &&
-                           state.latest_finalized == expected_post.latest_finalized {
-                            println!("  PASS: State transition successful");
-                        } else {
-                            println!("  FAIL: Post-state mismatch");
-                        }
-                    } else {
-                        println!("  PASS: Block processing completed");
-                    }
+                if post.config != test_vector.config {
+                    return Err(format!("{}: post-state config does not match the vector config", case).into());
                 }
             }
+
+            let (used, used_key, unused, unused_key) = match inputs {
+                CaseInputs::Blocks => (test_case.blocks.is_some(), "blocks", test_case.votes.is_some(), "votes"),
+                CaseInputs::Votes => (test_case.votes.is_some(), "votes", test_case.blocks.is_some(), "blocks"),
+            };
+            if !used {
+                return Err(format!("{}: missing `{}`", case, used_key).into());
+            }
+            if unused {
+                return Err(format!("{}: `{}` is ignored by this runner", case, unused_key).into());
+            }
+            if test_case.votes.as_ref().is_some_and(|votes| votes.len() > typenum::U4096::USIZE) {
+                return Err(format!("{}: more votes than fit in a block body", case).into());
+            }
         }
-        
-        Ok(())
+
+        Ok(test_vector)
     }
-    
+
+    pub fn run_state_transition_tests<P: AsRef<Path>>(path: P) -> Result<(), Box<dyn std::error::Error>> {
+        let path = path.as_ref();
+        let test_vector = Self::load_test_vector(path, CaseInputs::Blocks)?;
+
+        Self::run_cases(path, &test_vector, |test_case| {
+            let blocks = test_case.blocks.as_deref().unwrap_or_default();
+            blocks.iter().try_fold(test_case.pre.clone(), |state, block| {
+                state.state_transition(block.clone())
+            })
+        })
+    }
+
     pub fn run_vote_processing_tests<P: AsRef<Path>>(path: P) -> Result<(), Box<dyn std::error::Error>> {
-        let yaml_content = fs::read_to_string(path)?;
-        let test_vector: TestVector<State> = serde_yaml::from_str(&yaml_content)?;
-        
-        for (i, test_case) in test_vector.test_cases.iter().enumerate() {
-            println!("Running vote test {}: {}", i, test_case.description);
-            
-            if let Some(ref votes) = test_case.votes {
-                let state = test_case.pre.clone();
-                let mut attestations = ssz::PersistentList::default();
-                
-                // Convert votes to attestations list
-                for (idx, vote) in votes.iter().enumerate() {
-                    if idx < 4096 {
-                        attestations.push(vote.clone()).unwrap();
-                    }
-                }
-                
-                let new_state = state.process_attestations(&attestations);
-                
-                if let Some(ref expected_post) = test_case.post {
-                    if new_state.latest_justified == expected_post.latest_justified &&
-                       new_state.latest_finalized == expected_post.latest_finalized {
-                        println!("  PASS: Vote processing successful");
-                    } else {
-                        println!("  FAIL: Vote processing result mismatch");
-                        println!("    Expected justified: {:?}", expected_post.latest_justified);
-                        println!("    Actual justified: {:?}", new_state.latest_justified);
-                    }
+        let path = path.as_ref();
+        let test_vector = Self::load_test_vector(path, CaseInputs::Votes)?;
+
+        Self::run_cases(path, &test_vector, |test_case| {
+            let mut attestations = ssz::PersistentList::default();
+            for vote in test_case.votes.as_deref().unwrap_or_default() {
+                attestations.push(vote.clone()).expect("vote count is checked on load");
+            }
+            test_case.pre.process_attestations(&attestations)
+        })
+    }
+
+    pub fn run_block_processing_tests<P: AsRef<Path>>(path: P) -> Result<(), Box<dyn std::error::Error>> {
+        let path = path.as_ref();
+        let test_vector = Self::load_test_vector(path, CaseInputs::Blocks)?;
+
+        Self::run_cases(path, &test_vector, |test_case| {
+            let blocks = test_case.blocks.as_deref().unwrap_or_default();
+            blocks.iter().try_fold(test_case.pre.clone(), |state, block| {
+                // First process slots to advance to the block's slot, then process the block header
+                state
+                    .process_slots(block.message.slot)
+                    .and_then(|state_with_slots| state_with_slots.process_block_header(&block.message))
+            })
+        })
+    }
+
+    /// Runs every case, collecting failures instead of stopping at the first one.
+    /// `apply` returns the post-state, or the reason the inputs were rejected.
+    fn run_cases(
+        path: &Path,
+        test_vector: &TestVector<State>,
+        apply: impl Fn(&TestCase<State>) -> Result<State, StateTransitionError>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut failures = Vec::new();
+
+        for (index, test_case) in test_vector.test_cases.iter().enumerate() {
+            println!("Running test case {}: {}", index, test_case.description);
+
+            let outcome = Self::check_case(test_case, apply(test_case));
+            match outcome {
+                Ok(()) => println!("  PASS"),
+                Err(reason) => {
+                    println!("  FAIL: {}", reason);
+                    failures.push(CaseFailure { index, description: test_case.description.clone(), reason });
                 }
             }
         }
-        
-        Ok(())
+
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            Err(Box::new(TestFailures {
+                path: path.display().to_string(),
+                total: test_vector.test_cases.len(),
+                failures,
+            }))
+        }
     }
 
-    pub fn run_block_processing_tests<P: AsRef<Path>>(path: P) -> Result<(), Box<dyn std::error::Error>> {
-        let yaml_content = fs::read_to_string(path)?;
-        let test_vector: TestVector<State> = serde_yaml::from_str(&yaml_content)?;
-        
-        for (i, test_case) in test_vector.test_cases.iter().enumerate() {
-            println!("Running block processing test {}: {}", i, test_case.description);
-            
-            if let Some(ref blocks) = test_case.blocks {
-                let mut state = test_case.pre.clone();
-                let mut test_passed = true;
-                
-                for block in blocks {
-                    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
-                        // First process slots to advance to the block's slot
-                        let state_with_slots = state.process_slots(block.message.slot);
-                        // Then process the block header
-                        state_with_slots.process_block_header(&block.message)
-                    }));
-                    
-                    match result {
-                        Ok(new_state) => {
-                            if test_case.valid {
-                                state = new_state;
-                                println!("  Block processed successfully");
-                            } else {
-                                println!("  FAIL: Expected invalid block to be rejected");
-                                test_passed = false;
-                                break;
-                            }
-                        },
-                        Err(_) => {
-                            if !test_case.valid {
-                                println!("  PASS: Invalid block correctly rejected");
-                                continue;
-                            } else {
-                                println!("  FAIL: Valid block was rejected");
-                                test_passed = false;
-                                break;
-                            }
-                        }
-                    }
+    fn check_case(test_case: &TestCase<State>, result: Result<State, StateTransitionError>) -> Result<(), String> {
+        match (test_case.valid, result) {
+            (false, Ok(_)) => Err("expected the inputs to be rejected, but they were accepted".to_string()),
+            (false, Err(err)) => {
+                let expected = test_case.expected_error.as_deref().unwrap_or_default();
+                if error_variant(&err) == expected {
+                    Ok(())
+                } else {
+                    Err(format!("expected the inputs to be rejected with {}, got: {}", expected, err))
                 }
-                
-                if test_passed && test_case.valid {
-                    if let Some(ref expected_post) = test_case.post {
-                        if state.slot == expected_post.slot && 
-                           state.latest_block_header.slot == expected_post.latest_block_header.slot &&
-                           state.latest_justified == expected_post.latest_justified &&
-                           state.latest_finalized == expected_post.latest_finalized {
-                            println!("  PASS: Block processing successful");
-                        } else {
-                            println!("  FAIL: Post-state mismatch");
-                            println!("    Expected slot: {:?}, got: {:?}", expected_post.slot, state.slot);
-                            println!("    Expected header slot: {:?}, got: {:?}", 
-                                expected_post.latest_block_header.slot, state.latest_block_header.slot);
-                        }
+            }
+            (true, Err(err)) => Err(format!("valid inputs were rejected: {}", err)),
+            (true, Ok(actual)) => match test_case.post {
+                Some(ref expected) => {
+                    let expected_root = hash_tree_root(expected);
+                    let actual_root = hash_tree_root(&actual);
+                    if expected_root == actual_root {
+                        Ok(())
                     } else {
-                        println!("  PASS: Block processing completed");
+                        let mut reason = format!(
+                            "post-state root mismatch: expected {:?}, got {:?}",
+                            expected_root.0, actual_root.0
+                        );
+                        for line in diff_states(expected, &actual) {
+                            reason.push_str("\n      ");
+                            reason.push_str(&line);
+                        }
+                        Err(reason)
                     }
                 }
-            }
+                None => Ok(()),
+            },
+        }
+    }
+}
+
+/// Name of the error's variant, as vectors spell it in `expected_error`.
+pub fn error_variant(err: &StateTransitionError) -> String {
+    format!("{:?}", err).chars().take_while(|c| c.is_alphanumeric()).collect()
+}
+
+/// A single failing case of a test vector.
+#[derive(Debug)]
+pub struct CaseFailure {
+    pub index: usize,
+    pub description: String,
+    pub reason: String,
+}
+
+/// Every failing case of a test vector, reported together.
+#[derive(Debug)]
+pub struct TestFailures {
+    pub path: String,
+    pub total: usize,
+    pub failures: Vec<CaseFailure>,
+}
+
+impl std::fmt::Display for TestFailures {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} of {} test cases failed in {}", self.failures.len(), self.total, self.path)?;
+        for failure in &self.failures {
+            write!(f, "\n  case {} ({}): {}", failure.index, failure.description, failure.reason)?;
         }
-        
         Ok(())
     }
 }
+
+impl std::error::Error for TestFailures {}
+
+/// Lists every top-level field of the two states that differs.
+pub fn diff_states(expected: &State, actual: &State) -> Vec<String> {
+    fn field<T: PartialEq + std::fmt::Debug>(diffs: &mut Vec<String>, name: &str, expected: T, actual: T) {
+        if expected != actual {
+            diffs.push(format!("{}: expected {:?}, got {:?}", name, expected, actual));
+        }
+    }
+
+    fn votes(bits: &[bool]) -> Vec<usize> {
+        bits.iter().enumerate().filter(|(_, v)| **v).map(|(i, _)| i).collect()
+    }
+
+    let mut diffs = Vec::new();
+    field(&mut diffs, "config", &expected.config, &actual.config);
+    field(&mut diffs, "slot", expected.slot, actual.slot);
+    field(&mut diffs, "latest_block_header", &expected.latest_block_header, &actual.latest_block_header);
+    field(&mut diffs, "latest_justified", &expected.latest_justified, &actual.latest_justified);
+    field(&mut diffs, "latest_finalized", &expected.latest_finalized, &actual.latest_finalized);
+    field(
+        &mut diffs,
+        "historical_block_hashes",
+        roots_to_vec(&expected.historical_block_hashes),
+        roots_to_vec(&actual.historical_block_hashes),
+    );
+    field(&mut diffs, "justified_slots", bits_to_vec(&expected.justified_slots), bits_to_vec(&actual.justified_slots));
+    field(
+        &mut diffs,
+        "justifications_roots",
+        roots_to_vec(&expected.justifications_roots),
+        roots_to_vec(&actual.justifications_roots),
+    );
+    // Report the flattened validator bits per root, listing only the validators that voted.
+    // A malformed bit count is reported as its error instead.
+    let votes_by_root = |state: &State| {
+        state
+            .get_justifications()
+            .map(|map| map.into_iter().map(|(r, v)| (r, votes(&v))).collect::<BTreeMap<_, _>>())
+            .map_err(|e| e.to_string())
+    };
+    field(&mut diffs, "justifications_validators", votes_by_root(expected), votes_by_root(actual));
+    diffs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const JUSTIFICATION_PATH: &str = "test_vectors/vote_processing/justification.yaml";
+
+    fn justification_yaml() -> String {
+        fs::read_to_string(JUSTIFICATION_PATH).expect("vote processing fixture should exist")
+    }
+
+    #[test]
+    fn loads_full_pre_state_history() {
+        let vector = TestRunner::load_test_vector(JUSTIFICATION_PATH, CaseInputs::Votes).unwrap();
+        let pre = &vector.test_cases[0].pre;
+
+        assert_eq!(crate::state::roots_to_vec(&pre.historical_block_hashes).len(), 5);
+        assert_eq!(crate::state::bits_to_vec(&pre.justified_slots), vec![true, false, false, false, false]);
+    }
+
+    #[test]
+    fn rejects_unknown_state_keys() {
+        let yaml = justification_yaml().replacen(
+            "      justifications_roots: []",
+            "      justifications_roots: []\n      pending_votes: []",
+            1,
+        );
+
+        let err = TestRunner::parse_test_vector(&yaml, "inline", CaseInputs::Votes).unwrap_err();
+        assert!(err.to_string().contains("pending_votes"), "{}", err);
+    }
+
+    #[test]
+    fn rejects_unknown_case_keys() {
+        let yaml = justification_yaml().replacen("    valid: true", "    valid: true\n    expected_failure: none", 1);
+
+        let err = TestRunner::parse_test_vector(&yaml, "inline", CaseInputs::Votes).unwrap_err();
+        assert!(err.to_string().contains("expected_failure"), "{}", err);
+    }
+
+    #[test]
+    fn rejects_expected_error_outside_invalid_cases() {
+        let yaml = justification_yaml().replacen("    valid: true", "    valid: true\n    expected_error: IncorrectProposer", 1);
+        let err = TestRunner::parse_test_vector(&yaml, "inline", CaseInputs::Votes).unwrap_err();
+        assert!(err.to_string().contains("`expected_error`"), "{}", err);
+
+        let yaml = justification_yaml().replacen("    valid: true", "    valid: false", 1);
+        let err = TestRunner::parse_test_vector(&yaml, "inline", CaseInputs::Votes).unwrap_err();
+        assert!(err.to_string().contains("`expected_error`"), "{}", err);
+    }
+
+    #[test]
+    fn reports_rejections_for_another_reason() {
+        let yaml = fs::read_to_string("test_vectors/state_transition/basic.yaml")
+            .unwrap()
+            .replacen("expected_error: ParentRootMismatch", "expected_error: IncorrectProposer", 1);
+        let path = std::env::temp_dir().join(format!("lean_runner_wrong_error_{}.yaml", std::process::id()));
+        fs::write(&path, yaml).unwrap();
+
+        let err = TestRunner::run_state_transition_tests(&path).unwrap_err();
+        fs::remove_file(&path).unwrap();
+
+        let failures = err.downcast_ref::<TestFailures>().expect("mismatches should be reported as TestFailures");
+        assert_eq!(failures.failures.iter().map(|f| f.index).collect::<Vec<_>>(), vec![0]);
+        assert!(failures.failures[0].reason.contains("rejected with IncorrectProposer"), "{}", failures.failures[0].reason);
+        assert!(failures.failures[0].reason.contains("parent root mismatch"), "{}", failures.failures[0].reason);
+    }
+
+    #[test]
+    fn rejects_inputs_the_runner_ignores() {
+        let err = TestRunner::parse_test_vector(&justification_yaml(), "inline", CaseInputs::Blocks).unwrap_err();
+        assert!(err.to_string().contains("missing `blocks`"), "{}", err);
+    }
+
+    #[test]
+    fn reports_every_failing_case_with_field_diff() {
+        let yaml = justification_yaml().replace(
+            "      justifications_roots:\n        - \"0x4444",
+            "      justifications_roots:\n        - \"0x5555",
+        );
+        let path = std::env::temp_dir().join(format!("lean_runner_failures_{}.yaml", std::process::id()));
+        fs::write(&path, yaml).unwrap();
+
+        let err = TestRunner::run_vote_processing_tests(&path).unwrap_err();
+        fs::remove_file(&path).unwrap();
+
+        let failures = err.downcast_ref::<TestFailures>().expect("mismatches should be reported as TestFailures");
+        assert_eq!(failures.total, 2);
+        assert_eq!(failures.failures.iter().map(|f| f.index).collect::<Vec<_>>(), vec![0, 1]);
+        for failure in &failures.failures {
+            assert!(failure.reason.contains("post-state root mismatch"), "{}", failure.reason);
+            assert!(failure.reason.contains("justifications_roots"), "{}", failure.reason);
+            assert!(!failure.reason.contains("latest_justified"), "{}", failure.reason);
+        }
+    }
+
+    #[test]
+    fn rejects_config_mismatch() {
+        let yaml = justification_yaml().replacen("  num_validators: 10", "  num_validators: 12", 1);
+
+        let err = TestRunner::parse_test_vector(&yaml, "inline", CaseInputs::Votes).unwrap_err();
+        assert!(err.to_string().contains("config does not match"), "{}", err);
+    }
+}
@@ -7,7 +7,7 @@ mod tests {
         let test_path = "test_vectors/state_transition/basic.yaml";
         if std::path::Path::new(test_path).exists() {
             TestRunner::run_state_transition_tests(test_path)
-                .expect("State transition tests should pass");
+                .unwrap_or_else(|e| panic!("State transition tests should pass: {}", e));
         } else {
             println!("Test vector file not found, skipping: {}", test_path);
         }
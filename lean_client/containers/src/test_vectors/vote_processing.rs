@@ -7,7 +7,7 @@ mod tests {
         let test_path = "test_vectors/vote_processing/justification.yaml";
         if std::path::Path::new(test_path).exists() {
             TestRunner::run_vote_processing_tests(test_path)
-                .expect("Vote processing tests should pass");
+                .unwrap_or_else(|e| panic!("Vote processing tests should pass: {}", e));
         } else {
             println!("Test vector file not found, skipping: {}", test_path);
         }
@@ -10,6 +10,6 @@ pub struct Bytes32(pub H256);
 #[ssz(transparent)]
 pub struct Uint64(pub u64);
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Ssz, Default, Serialize, Deserialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Ssz, Default, Serialize, Deserialize)]
 #[ssz(transparent)]
 pub struct ValidatorIndex(pub u64);
\ No newline at end of file
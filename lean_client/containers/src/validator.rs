@@ -0,0 +1,10 @@
+use crate::PublicKey;
+use ssz_derive::Ssz;
+use serde::{Deserialize, Serialize};
+
+/// Registry entry for a validator, stored in `State::validators` at its `ValidatorIndex`.
+#[derive(Clone, Debug, PartialEq, Eq, Ssz, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Validator {
+    pub pubkey: PublicKey,
+}
@@ -1,8 +1,9 @@
-use crate::{Bytes32, Slot, Uint64, Checkpoint};
+use crate::{Signature, Slot, Uint64, Checkpoint};
 use ssz_derive::Ssz;
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Debug, PartialEq, Eq, Ssz, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct Vote {
     pub validator_id: Uint64,
     pub slot: Slot,
@@ -12,7 +13,9 @@ pub struct Vote {
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Ssz, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct SignedVote {
     pub data: Vote,
-    pub signature: Bytes32, //placeholder
+    /// Over `hash_tree_root(data)`, at `vote_signing_epoch(data.slot)`.
+    pub signature: Signature,
 }
\ No newline at end of file
@@ -1,20 +1,38 @@
+// Shared by every test binary via `#[path = "common.rs"]`; not every binary uses every helper.
+#![allow(dead_code)]
+
 use containers::{
     block::{Block, BlockBody, BlockHeader, SignedBlock, hash_tree_root},
     checkpoint::Checkpoint,
     config::Config,
+    signature::{PublicKey, Signature},
     slot::Slot,
     state::State,
-    types::{Bytes32, ValidatorIndex},
-    vote::{SignedVote},
+    types::{Bytes32, Uint64, ValidatorIndex},
+    vote::SignedVote,
 };
 use ssz::PersistentList as List;
 use typenum::U4096;
 
+#[allow(unused_imports)]
+pub use test_support::{sign_block, sign_vote};
+
 pub const DEVNET_CONFIG_VALIDATOR_REGISTRY_LIMIT: usize = 1 << 12; // 4096
 
+/// Keys of the `sample_config` validators, see `test_support::test_key`.
+pub fn test_pubkeys() -> Vec<PublicKey> {
+    test_support::test_pubkeys(sample_config().num_validators)
+}
+
+/// Genesis for `sample_config` with the test keys registered.
+pub fn genesis_state() -> State {
+    let config = sample_config();
+    State::generate_genesis(Uint64(config.genesis_time), &test_pubkeys())
+}
+
 pub fn create_block(slot: u64, parent_header: &mut BlockHeader, votes: Option<List<SignedVote, U4096>>) -> SignedBlock {
     let body = BlockBody {
-    attestations: votes.unwrap_or_else(List::default),
+    attestations: votes.unwrap_or_default(),
     };
 
     let block_message = Block {
@@ -27,7 +45,7 @@ pub fn create_block(slot: u64, parent_header: &mut BlockHeader, votes: Option<Li
 
     SignedBlock {
         message: block_message,
-    signature: Bytes32(ssz::H256::zero()),
+    signature: Signature::default(),
     }
 }
 
@@ -65,10 +83,11 @@ pub fn base_state(config: Config) -> State {
         latest_block_header: sample_block_header(),
         latest_justified: sample_checkpoint(),
         latest_finalized: sample_checkpoint(),
-        historical_block_hashes: Vec::new(),
-        justified_slots: Vec::new(),
-        justifications_roots: Vec::new(),
-        justifications_validators: Vec::new(),
+        historical_block_hashes: List::default(),
+        justified_slots: ssz::BitList::default(),
+        validators: List::default(),
+        justifications_roots: List::default(),
+        justifications_validators: ssz::BitList::default(),
     }
 }
 
@@ -1,5 +1,10 @@
 // tests/main.rs
+// Each test module also pulls in `common.rs` itself so it can build as a standalone binary.
+#![allow(clippy::duplicate_mod)]
+
 mod common;
+mod signature;
+mod ssz_static;
 mod state_basic;
 mod state_justifications;
 mod state_process;
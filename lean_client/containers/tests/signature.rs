@@ -0,0 +1,60 @@
+// tests/signature.rs
+use containers::{
+    signature::{block_signing_epoch, vote_signing_epoch, SignatureError, SignatureScheme, Xmss},
+    types::Bytes32,
+    Slot,
+};
+use ssz::H256;
+
+fn message(byte: u8) -> Bytes32 {
+    Bytes32(H256([byte; 32]))
+}
+
+#[test]
+fn test_sign_and_verify_every_epoch() {
+    let (pubkey, secret_key) = Xmss.generate_key([7; 32], 3);
+    assert_eq!(secret_key.lifetime(), 8);
+
+    for epoch in 0..8 {
+        let signature = Xmss.sign(&secret_key, epoch, message(1)).unwrap();
+        assert!(Xmss.verify(&pubkey, epoch, message(1), &signature), "epoch {}", epoch);
+    }
+}
+
+#[test]
+fn test_signature_is_bound_to_key_epoch_and_message() {
+    let (pubkey, secret_key) = Xmss.generate_key([7; 32], 2);
+    let (other_pubkey, _) = Xmss.generate_key([8; 32], 2);
+    let signature = Xmss.sign(&secret_key, 1, message(1)).unwrap();
+
+    assert!(Xmss.verify(&pubkey, 1, message(1), &signature));
+    assert!(!Xmss.verify(&pubkey, 2, message(1), &signature));
+    assert!(!Xmss.verify(&pubkey, 1, message(2), &signature));
+    assert!(!Xmss.verify(&other_pubkey, 1, message(1), &signature));
+    assert!(!Xmss.verify(&pubkey, 1, message(1), &Default::default()));
+
+    let mut tampered = signature.clone();
+    let first_chain = *tampered.chains.get(0).unwrap();
+    *tampered.chains.get_mut(0).unwrap() = Bytes32(H256([!first_chain.0[0]; 32]));
+    assert!(!Xmss.verify(&pubkey, 1, message(1), &tampered));
+}
+
+#[test]
+fn test_key_generation_is_deterministic() {
+    let (pubkey, secret_key) = Xmss.generate_key([3; 32], 2);
+    assert_eq!(Xmss.generate_key([3; 32], 2).0, pubkey);
+    assert_eq!(secret_key.public_key(), pubkey);
+    assert_ne!(Xmss.generate_key([4; 32], 2).0, pubkey);
+}
+
+#[test]
+fn test_sign_rejects_epoch_past_key_lifetime() {
+    let (_, secret_key) = Xmss.generate_key([7; 32], 2);
+    assert_eq!(Xmss.sign(&secret_key, 4, message(1)), Err(SignatureError::EpochOutOfRange { epoch: 4, lifetime: 4 }));
+}
+
+#[test]
+fn test_blocks_and_votes_use_separate_epochs() {
+    assert_eq!(block_signing_epoch(Slot(3)), 6);
+    assert_eq!(vote_signing_epoch(Slot(3)), 7);
+}
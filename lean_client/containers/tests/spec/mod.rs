@@ -0,0 +1,297 @@
+// tests/spec/mod.rs
+//! Loader for leanSpec-generated consensus fixtures.
+//!
+//! Cases are directories of `*.ssz_snappy` files below a `state_transition`,
+//! `fork_choice` or `ssz_static` directory; everything in between (fork, suite,
+//! case name) only contributes to the test name.
+use containers::{
+    block::{hash_tree_root, Block, BlockBody, BlockHeader, SignedBlock},
+    ssz::{SszHash, SszReadDefault, SszWrite},
+    Bytes32, Checkpoint, ContainerConfig, PublicKey, Signature, SignedVote, State, Validator, Vote,
+};
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Overrides the fixture root, e.g. to point at a leanSpec `fixtures/consensus` checkout.
+pub const FIXTURES_ENV: &str = "LEAN_SPEC_FIXTURES";
+
+/// Container types the `ssz_static` runner knows how to check.
+pub const SSZ_STATIC_TYPES: &[&str] = &[
+    "Block", "BlockBody", "BlockHeader", "Checkpoint", "Config", "PublicKey", "SignedBlock", "SignedVote", "Signature",
+    "State", "Validator", "Vote",
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Runner {
+    StateTransition,
+    ForkChoice,
+    SszStatic,
+}
+
+impl Runner {
+    fn from_dir_name(name: &str) -> Option<Self> {
+        match name {
+            "state_transition" => Some(Runner::StateTransition),
+            "fork_choice" => Some(Runner::ForkChoice),
+            "ssz_static" => Some(Runner::SszStatic),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Runner::StateTransition => "state_transition",
+            Runner::ForkChoice => "fork_choice",
+            Runner::SszStatic => "ssz_static",
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct SpecCase {
+    pub runner: Runner,
+    /// `runner::path::to::case`, unique within a fixture root.
+    pub name: String,
+    pub dir: PathBuf,
+    /// Path components between the runner directory and the case directory.
+    pub components: Vec<String>,
+}
+
+pub fn fixtures_root() -> PathBuf {
+    std::env::var_os(FIXTURES_ENV)
+        .map(PathBuf::from)
+        .unwrap_or_else(|| Path::new(env!("CARGO_MANIFEST_DIR")).join("test_vectors/consensus"))
+}
+
+/// Finds every case directory below `root`, sorted by name.
+/// Fails if `root` is missing or holds no case, so a mistyped `$LEAN_SPEC_FIXTURES` can't pass
+/// by running nothing.
+pub fn discover(root: &Path) -> Result<Vec<SpecCase>, String> {
+    if !root.is_dir() {
+        return Err(format!("{}: fixture root is not a directory", root.display()));
+    }
+    let mut cases = Vec::new();
+    walk(root, None, &mut Vec::new(), &mut cases)?;
+    if cases.is_empty() {
+        return Err(format!("{}: no fixture cases found", root.display()));
+    }
+    cases.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(cases)
+}
+
+fn walk(dir: &Path, runner: Option<Runner>, components: &mut Vec<String>, cases: &mut Vec<SpecCase>) -> Result<(), String> {
+    let mut entries = fs::read_dir(dir)
+        .map_err(|e| format!("{}: {}", dir.display(), e))?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("{}: {}", dir.display(), e))?;
+    entries.sort();
+
+    let is_case = entries.iter().any(|p| p.is_file() && has_extension(p, "ssz_snappy"));
+    if let (true, Some(runner)) = (is_case, runner) {
+        let mut name = runner.name().to_string();
+        for component in components.iter() {
+            name.push_str("::");
+            name.push_str(component);
+        }
+        cases.push(SpecCase { runner, name, dir: dir.to_path_buf(), components: components.clone() });
+        return Ok(());
+    }
+
+    for path in entries.iter().filter(|p| p.is_dir()) {
+        let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or_default().to_string();
+        match runner {
+            None => walk(path, Runner::from_dir_name(&file_name), components, cases)?,
+            Some(_) => {
+                components.push(file_name);
+                walk(path, runner, components, cases)?;
+                components.pop();
+            }
+        }
+    }
+    Ok(())
+}
+
+fn has_extension(path: &Path, extension: &str) -> bool {
+    path.extension().and_then(|e| e.to_str()) == Some(extension)
+}
+
+pub fn read_ssz_snappy<T: SszReadDefault>(path: &Path) -> Result<T, String> {
+    let bytes = read_ssz_snappy_bytes(path)?;
+    T::from_ssz_default(bytes).map_err(|e| format!("{}: invalid SSZ: {:?}", path.display(), e))
+}
+
+pub fn read_ssz_snappy_bytes(path: &Path) -> Result<Vec<u8>, String> {
+    let compressed = fs::read(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    snap::raw::Decoder::new()
+        .decompress_vec(&compressed)
+        .map_err(|e| format!("{}: invalid snappy: {}", path.display(), e))
+}
+
+/// `{prefix}_0.ssz_snappy`, `{prefix}_1.ssz_snappy`, ... in numeric order.
+/// Fails on a gap so a missing file can't silently shorten the sequence.
+pub fn numbered_files(dir: &Path, prefix: &str) -> Result<Vec<PathBuf>, String> {
+    let mut numbered = Vec::new();
+    for entry in fs::read_dir(dir).map_err(|e| format!("{}: {}", dir.display(), e))? {
+        let path = entry.map_err(|e| format!("{}: {}", dir.display(), e))?.path();
+        let index = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .and_then(|n| n.strip_suffix(".ssz_snappy"))
+            .and_then(|n| n.strip_prefix(prefix))
+            .and_then(|n| n.strip_prefix('_'))
+            .and_then(|n| n.parse::<usize>().ok());
+        if let Some(index) = index {
+            numbered.push((index, path));
+        }
+    }
+    numbered.sort();
+    for (expected, (index, path)) in numbered.iter().enumerate() {
+        if *index != expected {
+            return Err(format!("{}: expected {}_{}.ssz_snappy", path.display(), prefix, expected));
+        }
+    }
+    Ok(numbered.into_iter().map(|(_, path)| path).collect())
+}
+
+impl SpecCase {
+    pub fn run(&self) -> Result<(), String> {
+        match self.runner {
+            Runner::StateTransition => run_state_transition(&self.dir),
+            Runner::ForkChoice => decode_fork_choice(&self.dir),
+            Runner::SszStatic => run_ssz_static(self),
+        }
+    }
+
+    /// The container type of an `ssz_static` case, taken from its path.
+    pub fn ssz_type(&self) -> Option<&'static str> {
+        self.components
+            .iter()
+            .find_map(|c| SSZ_STATIC_TYPES.iter().copied().find(|t| t == c))
+    }
+}
+
+/// `pre` + `blocks_N` must reach `post`; without a `post` file the last block must be
+/// rejected and every block before it accepted.
+fn run_state_transition(dir: &Path) -> Result<(), String> {
+    let pre: State = read_ssz_snappy(&dir.join("pre.ssz_snappy"))?;
+    let mut blocks = numbered_files(dir, "blocks")?
+        .iter()
+        .map(|path| read_ssz_snappy::<SignedBlock>(path))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let post_path = dir.join("post.ssz_snappy");
+    let invalid_block = if post_path.exists() { None } else { blocks.pop() };
+    if !post_path.exists() && invalid_block.is_none() {
+        return Err("expected a block to be rejected, but the case has none".to_string());
+    }
+
+    let mut state = pre;
+    for (i, block) in blocks.into_iter().enumerate() {
+        state = state
+            .state_transition(block)
+            .map_err(|e| format!("valid block {} was rejected: {}", i, e))?;
+    }
+
+    if let Some(block) = invalid_block {
+        return match state.state_transition(block) {
+            Ok(_) => Err("expected the last block to be rejected, but it was accepted".to_string()),
+            Err(_) => Ok(()),
+        };
+    }
+
+    let expected: State = read_ssz_snappy(&post_path)?;
+    let actual = state;
+    let (expected_root, actual_root) = (hash_tree_root(&expected), hash_tree_root(&actual));
+    if expected_root != actual_root {
+        return Err(format!(
+            "post-state root mismatch: expected {:?}, got {:?} (slot {} vs {}, justified {:?} vs {:?}, finalized {:?} vs {:?})",
+            expected_root.0, actual_root.0,
+            expected.slot.0, actual.slot.0,
+            expected.latest_justified, actual.latest_justified,
+            expected.latest_finalized, actual.latest_finalized,
+        ));
+    }
+    Ok(())
+}
+
+/// Fork choice is not driven from this crate, so these cases are registered as ignored;
+/// run with `--include-ignored` to check that every input of the case decodes.
+fn decode_fork_choice(dir: &Path) -> Result<(), String> {
+    read_ssz_snappy::<State>(&dir.join("anchor_state.ssz_snappy"))?;
+    read_ssz_snappy::<Block>(&dir.join("anchor_block.ssz_snappy"))?;
+
+    let steps_path = dir.join("steps.yaml");
+    let steps = fs::read_to_string(&steps_path).map_err(|e| format!("{}: {}", steps_path.display(), e))?;
+    let steps: serde_yaml::Value = serde_yaml::from_str(&steps).map_err(|e| format!("{}: {}", steps_path.display(), e))?;
+    if !steps.is_sequence() {
+        return Err(format!("{}: expected a list of steps", steps_path.display()));
+    }
+
+    for entry in fs::read_dir(dir).map_err(|e| format!("{}: {}", dir.display(), e))? {
+        let path = entry.map_err(|e| format!("{}: {}", dir.display(), e))?.path();
+        let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+        if file_name.starts_with("block_") {
+            read_ssz_snappy::<SignedBlock>(&path)?;
+        } else if file_name.starts_with("attestation_") {
+            read_ssz_snappy::<SignedVote>(&path)?;
+        }
+    }
+    Ok(())
+}
+
+/// Expected hash tree root of an `ssz_static` case.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Roots {
+    root: Bytes32,
+}
+
+fn run_ssz_static(case: &SpecCase) -> Result<(), String> {
+    let ssz_type = case
+        .ssz_type()
+        .ok_or_else(|| format!("{}: no known container type in path", case.dir.display()))?;
+
+    match ssz_type {
+        "Block" => check_ssz_static::<Block>(&case.dir),
+        "BlockBody" => check_ssz_static::<BlockBody>(&case.dir),
+        "BlockHeader" => check_ssz_static::<BlockHeader>(&case.dir),
+        "Checkpoint" => check_ssz_static::<Checkpoint>(&case.dir),
+        "Config" => check_ssz_static::<ContainerConfig>(&case.dir),
+        "PublicKey" => check_ssz_static::<PublicKey>(&case.dir),
+        "SignedBlock" => check_ssz_static::<SignedBlock>(&case.dir),
+        "SignedVote" => check_ssz_static::<SignedVote>(&case.dir),
+        "Signature" => check_ssz_static::<Signature>(&case.dir),
+        "State" => check_ssz_static::<State>(&case.dir),
+        "Validator" => check_ssz_static::<Validator>(&case.dir),
+        "Vote" => check_ssz_static::<Vote>(&case.dir),
+        other => Err(format!("unsupported ssz_static type {}", other)),
+    }
+}
+
+/// Decodes `serialized`, re-encodes it byte for byte and checks the root in `roots.yaml`.
+fn check_ssz_static<T: SszReadDefault + SszWrite + SszHash>(dir: &Path) -> Result<(), String> {
+    let serialized_path = dir.join("serialized.ssz_snappy");
+    let serialized = read_ssz_snappy_bytes(&serialized_path)?;
+    let value = T::from_ssz_default(&serialized)
+        .map_err(|e| format!("{}: invalid SSZ: {:?}", serialized_path.display(), e))?;
+
+    let reencoded = value.to_ssz().map_err(|e| format!("re-encoding failed: {:?}", e))?;
+    if reencoded != serialized {
+        return Err(format!(
+            "re-encoded bytes differ: {} bytes in fixture, {} bytes re-encoded",
+            serialized.len(),
+            reencoded.len()
+        ));
+    }
+
+    let roots_path = dir.join("roots.yaml");
+    let roots = fs::read_to_string(&roots_path).map_err(|e| format!("{}: {}", roots_path.display(), e))?;
+    let roots: Roots = serde_yaml::from_str(&roots).map_err(|e| format!("{}: {}", roots_path.display(), e))?;
+    let root = hash_tree_root(&value);
+    if root != roots.root {
+        return Err(format!("hash_tree_root mismatch: expected {:?}, got {:?}", roots.root.0, root.0));
+    }
+    Ok(())
+}
@@ -0,0 +1,217 @@
+// tests/spec_tests.rs
+//! Runs leanSpec consensus fixtures, one test per case directory.
+//!
+//! Fixtures are read from `test_vectors/consensus` or from `$LEAN_SPEC_FIXTURES`.
+//! `fork_choice` cases are listed as ignored: fork choice lives in the downstream
+//! `fork_choice` crate, so here they are only decoded, with `--include-ignored`.
+use containers::{
+    block::{hash_tree_root, Block, SignedBlock},
+    ssz::{SszHash, SszWrite},
+    state::State,
+    Bytes32, Slot,
+};
+use libtest_mimic::{Arguments, Failed, Trial};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[path = "common.rs"]
+mod common;
+mod spec;
+
+use common::{create_block, genesis_state, sign_block};
+use spec::{discover, fixtures_root, Runner};
+
+fn main() {
+    let args = Arguments::from_args();
+
+    let mut trials = loader_trials();
+    let root = fixtures_root();
+    match discover(&root) {
+        Ok(cases) => trials.extend(cases.into_iter().map(|case| {
+            let kind = case.runner.name();
+            let decode_only = case.runner == Runner::ForkChoice;
+            Trial::test(case.name.clone(), move || case.run().map_err(Failed::from))
+                .with_kind(kind)
+                .with_ignored_flag(decode_only)
+        })),
+        Err(err) => trials.push(Trial::test("discover", move || Err(Failed::from(err)))),
+    }
+
+    libtest_mimic::run(&args, trials).exit();
+}
+
+// --- loader self-checks against a generated fixture tree ---
+
+fn loader_trials() -> Vec<Trial> {
+    vec![
+        Trial::test("loader::discovers_cases_by_runner", discovers_cases_by_runner),
+        Trial::test("loader::rejects_missing_or_empty_root", rejects_missing_or_empty_root),
+        Trial::test("loader::runs_valid_and_invalid_state_transitions", runs_valid_and_invalid_state_transitions),
+        Trial::test("loader::rejects_wrong_post_state", rejects_wrong_post_state),
+        Trial::test("loader::rejects_gaps_in_block_numbering", rejects_gaps_in_block_numbering),
+        Trial::test("loader::checks_ssz_static_bytes_and_root", checks_ssz_static_bytes_and_root),
+        Trial::test("loader::finds_vendored_fixtures", finds_vendored_fixtures),
+    ]
+}
+
+fn write_ssz_snappy<T: SszWrite>(path: &Path, value: &T) {
+    let bytes = value.to_ssz().expect("fixture values should serialize");
+    let compressed = snap::raw::Encoder::new().compress_vec(&bytes).unwrap();
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, compressed).unwrap();
+}
+
+fn scratch_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("lean_spec_fixtures_{}_{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    dir
+}
+
+/// Genesis, a valid slot-1 block and the state it produces.
+fn valid_chain() -> (State, SignedBlock, State) {
+    let genesis = genesis_state();
+    let mut state_at_slot_1 = genesis.process_slots(Slot(1)).unwrap();
+    let block = create_block(1, &mut state_at_slot_1.latest_block_header, None).message;
+    let post = state_at_slot_1.process_block(&block).unwrap();
+    let signed_block = sign_block(Block { state_root: hash_tree_root(&post), ..block });
+    (genesis, signed_block, post)
+}
+
+fn write_state_transition_case(dir: &Path, pre: &State, blocks: &[SignedBlock], post: Option<&State>) {
+    write_ssz_snappy(&dir.join("pre.ssz_snappy"), pre);
+    for (i, block) in blocks.iter().enumerate() {
+        write_ssz_snappy(&dir.join(format!("blocks_{}.ssz_snappy", i)), block);
+    }
+    if let Some(post) = post {
+        write_ssz_snappy(&dir.join("post.ssz_snappy"), post);
+    }
+}
+
+fn discovers_cases_by_runner() -> Result<(), Failed> {
+    let root = scratch_dir("discover");
+    let (genesis, block, post) = valid_chain();
+    write_state_transition_case(&root.join("state_transition/devnet/blocks/single_block"), &genesis, &[block], Some(&post));
+    write_ssz_snappy(&root.join("ssz_static/devnet/Checkpoint/ssz_random/case_0/serialized.ssz_snappy"), &genesis.latest_justified);
+    write_ssz_snappy(&root.join("unrelated/devnet/case_0/pre.ssz_snappy"), &genesis);
+
+    let cases = discover(&root)?;
+    fs::remove_dir_all(&root).unwrap();
+
+    let names: Vec<_> = cases.iter().map(|c| (c.runner, c.name.as_str())).collect();
+    if names
+        != [
+            (Runner::SszStatic, "ssz_static::devnet::Checkpoint::ssz_random::case_0"),
+            (Runner::StateTransition, "state_transition::devnet::blocks::single_block"),
+        ]
+    {
+        return Err(format!("unexpected cases: {:?}", names).into());
+    }
+    if cases[0].ssz_type() != Some("Checkpoint") {
+        return Err("ssz_static type should come from the path".into());
+    }
+    Ok(())
+}
+
+fn rejects_missing_or_empty_root() -> Result<(), Failed> {
+    let root = scratch_dir("empty");
+    let missing = discover(&root);
+    write_ssz_snappy(&root.join("unrelated/devnet/case_0/pre.ssz_snappy"), &genesis_state());
+    let empty = discover(&root);
+    fs::remove_dir_all(&root).unwrap();
+
+    match (missing, empty) {
+        (Err(missing), Err(empty)) if missing.contains("not a directory") && empty.contains("no fixture cases") => Ok(()),
+        other => Err(format!("unexpected results: {:?}", other).into()),
+    }
+}
+
+fn runs_valid_and_invalid_state_transitions() -> Result<(), Failed> {
+    let root = scratch_dir("state_transition");
+    let (genesis, block, post) = valid_chain();
+    let mut wrong_proposer = block.clone();
+    wrong_proposer.message.proposer_index = containers::ValidatorIndex(7);
+    write_state_transition_case(&root.join("state_transition/devnet/valid"), &genesis, &[block], Some(&post));
+    write_state_transition_case(&root.join("state_transition/devnet/invalid"), &genesis, &[wrong_proposer], None);
+
+    let results: Vec<_> = discover(&root)?.iter().map(|case| (case.name.clone(), case.run())).collect();
+    fs::remove_dir_all(&root).unwrap();
+
+    for (name, result) in results {
+        result.map_err(|e| format!("{}: {}", name, e))?;
+    }
+    Ok(())
+}
+
+fn rejects_wrong_post_state() -> Result<(), Failed> {
+    let root = scratch_dir("wrong_post");
+    let (genesis, block, mut post) = valid_chain();
+    post.slot = Slot(2);
+    write_state_transition_case(&root.join("state_transition/devnet/wrong_post"), &genesis, std::slice::from_ref(&block), Some(&post));
+    write_state_transition_case(&root.join("state_transition/devnet/should_be_invalid"), &genesis, std::slice::from_ref(&block), None);
+    // The rejection has to come from the last block, not from an earlier one
+    let mut wrong_proposer = block.clone();
+    wrong_proposer.message.proposer_index = containers::ValidatorIndex(7);
+    write_state_transition_case(&root.join("state_transition/devnet/wrong_block_rejected"), &genesis, &[wrong_proposer, block], None);
+
+    let results: Vec<_> = discover(&root)?.iter().map(|case| case.run()).collect();
+    fs::remove_dir_all(&root).unwrap();
+
+    match results.as_slice() {
+        [Err(invalid), Err(early), Err(mismatch)]
+            if invalid.contains("expected the last block to be rejected")
+                && mismatch.contains("post-state root mismatch")
+                && early.contains("valid block 0 was rejected") =>
+        {
+            Ok(())
+        }
+        other => Err(format!("unexpected results: {:?}", other).into()),
+    }
+}
+
+fn rejects_gaps_in_block_numbering() -> Result<(), Failed> {
+    let root = scratch_dir("gaps");
+    let (genesis, block, post) = valid_chain();
+    let dir = root.join("state_transition/devnet/gap");
+    write_state_transition_case(&dir, &genesis, &[], Some(&post));
+    write_ssz_snappy(&dir.join("blocks_1.ssz_snappy"), &block);
+
+    let result = discover(&root)?[0].run();
+    fs::remove_dir_all(&root).unwrap();
+
+    match result {
+        Err(err) if err.contains("expected blocks_0.ssz_snappy") => Ok(()),
+        other => Err(format!("unexpected result: {:?}", other).into()),
+    }
+}
+
+fn write_ssz_static_case<T: SszWrite + SszHash>(dir: &Path, value: &T, root: Bytes32) {
+    write_ssz_snappy(&dir.join("serialized.ssz_snappy"), value);
+    fs::write(dir.join("roots.yaml"), format!("root: '{:?}'\n", root.0)).unwrap();
+}
+
+fn checks_ssz_static_bytes_and_root() -> Result<(), Failed> {
+    let root = scratch_dir("ssz_static");
+    let (genesis, block, _) = valid_chain();
+    let dir = root.join("ssz_static/devnet");
+    write_ssz_static_case(&dir.join("SignedBlock/ssz_random/case_0"), &block, hash_tree_root(&block));
+    write_ssz_static_case(&dir.join("State/ssz_random/case_0"), &genesis, hash_tree_root(&genesis));
+    write_ssz_static_case(&dir.join("Vote/ssz_random/wrong_root"), &containers::Vote::default(), hash_tree_root(&block));
+
+    let results: Vec<_> = discover(&root)?.iter().map(|case| (case.name.clone(), case.run())).collect();
+    fs::remove_dir_all(&root).unwrap();
+
+    match results.as_slice() {
+        [(_, Ok(())), (_, Ok(())), (_, Err(mismatch))] if mismatch.contains("hash_tree_root mismatch") => Ok(()),
+        other => Err(format!("unexpected results: {:?}", other).into()),
+    }
+}
+
+fn finds_vendored_fixtures() -> Result<(), Failed> {
+    let vendored = Path::new(env!("CARGO_MANIFEST_DIR")).join("test_vectors/consensus");
+    let cases = discover(&vendored)?;
+    let state_transitions = cases.iter().filter(|case| case.runner == Runner::StateTransition).count();
+    if state_transitions < 4 {
+        return Err(format!("expected the vendored state_transition cases, found {}", state_transitions).into());
+    }
+    Ok(())
+}
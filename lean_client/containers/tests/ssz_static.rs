@@ -0,0 +1,516 @@
+// tests/ssz_static.rs
+//! SSZ checks for every container: fixed instances against known bytes and
+//! hash tree roots, and randomized round trips that must decode back to
+//! themselves, re-encode to the same bytes and keep their root.
+//!
+//! The known answers were worked out from the SSZ spec (little-endian
+//! offsets, zero-padded merkleization, `mix_in_length` for lists and
+//! bitlists) by a standalone reference encoder, independently of the `ssz`
+//! crate used here.
+use containers::{
+    block::{hash_tree_root, Block, BlockBody, BlockHeader, SignedBlock},
+    ssz::{PersistentList as List, SszHash, SszReadDefault, SszWrite, H256},
+    state::{bits_from_slice, roots_from_slice, State, VALIDATOR_REGISTRY_LIMIT},
+    Bytes32, Checkpoint, ContainerConfig, PublicKey, Signature, SignedVote, Slot, Uint64, Validator, ValidatorIndex, Vote,
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::fmt::Debug;
+
+const SEED: u64 = 0x1ea4_55a2;
+const ITERATIONS: usize = 32;
+
+/// Builds a random instance of a container.
+trait Random {
+    fn random(rng: &mut StdRng) -> Self;
+}
+
+impl Random for Bytes32 {
+    fn random(rng: &mut StdRng) -> Self {
+        Bytes32(H256(rng.gen()))
+    }
+}
+
+impl Random for Slot {
+    fn random(rng: &mut StdRng) -> Self {
+        Slot(rng.gen())
+    }
+}
+
+impl Random for Checkpoint {
+    fn random(rng: &mut StdRng) -> Self {
+        Checkpoint { root: Bytes32::random(rng), slot: Slot::random(rng) }
+    }
+}
+
+impl Random for ContainerConfig {
+    fn random(rng: &mut StdRng) -> Self {
+        ContainerConfig { num_validators: rng.gen(), genesis_time: rng.gen() }
+    }
+}
+
+impl Random for PublicKey {
+    fn random(rng: &mut StdRng) -> Self {
+        PublicKey { root: Bytes32::random(rng), public_seed: Bytes32::random(rng) }
+    }
+}
+
+impl Random for Validator {
+    fn random(rng: &mut StdRng) -> Self {
+        Validator { pubkey: PublicKey::random(rng) }
+    }
+}
+
+impl Random for Signature {
+    fn random(rng: &mut StdRng) -> Self {
+        let mut signature = Signature::default();
+        for _ in 0..rng.gen_range(0..=67) {
+            signature.chains.push(Bytes32::random(rng)).unwrap();
+        }
+        for _ in 0..rng.gen_range(0..=32) {
+            signature.auth_path.push(Bytes32::random(rng)).unwrap();
+        }
+        signature
+    }
+}
+
+impl Random for Vote {
+    fn random(rng: &mut StdRng) -> Self {
+        Vote {
+            validator_id: Uint64(rng.gen()),
+            slot: Slot::random(rng),
+            head: Checkpoint::random(rng),
+            target: Checkpoint::random(rng),
+            source: Checkpoint::random(rng),
+        }
+    }
+}
+
+impl Random for SignedVote {
+    fn random(rng: &mut StdRng) -> Self {
+        SignedVote { data: Vote::random(rng), signature: Signature::random(rng) }
+    }
+}
+
+impl Random for BlockBody {
+    fn random(rng: &mut StdRng) -> Self {
+        let mut attestations = List::default();
+        for _ in 0..rng.gen_range(0..4) {
+            attestations.push(SignedVote::random(rng)).unwrap();
+        }
+        BlockBody { attestations }
+    }
+}
+
+impl Random for BlockHeader {
+    fn random(rng: &mut StdRng) -> Self {
+        BlockHeader {
+            slot: Slot::random(rng),
+            proposer_index: ValidatorIndex(rng.gen()),
+            parent_root: Bytes32::random(rng),
+            state_root: Bytes32::random(rng),
+            body_root: Bytes32::random(rng),
+        }
+    }
+}
+
+impl Random for Block {
+    fn random(rng: &mut StdRng) -> Self {
+        Block {
+            slot: Slot::random(rng),
+            proposer_index: ValidatorIndex(rng.gen()),
+            parent_root: Bytes32::random(rng),
+            state_root: Bytes32::random(rng),
+            body: BlockBody::random(rng),
+        }
+    }
+}
+
+impl Random for SignedBlock {
+    fn random(rng: &mut StdRng) -> Self {
+        SignedBlock { message: Block::random(rng), signature: Signature::random(rng) }
+    }
+}
+
+impl Random for State {
+    fn random(rng: &mut StdRng) -> Self {
+        let random_roots = |rng: &mut StdRng, len: usize| (0..len).map(|_| Bytes32::random(rng)).collect::<Vec<_>>();
+        let random_bits = |rng: &mut StdRng, len: usize| (0..len).map(|_| rng.gen_bool(0.5)).collect::<Vec<_>>();
+
+        let history = rng.gen_range(0..8);
+        let pending = rng.gen_range(0..3);
+        let mut validators = List::default();
+        for _ in 0..rng.gen_range(0..4) {
+            validators.push(Validator::random(rng)).unwrap();
+        }
+        State {
+            config: ContainerConfig::random(rng),
+            slot: Slot::random(rng),
+            latest_block_header: BlockHeader::random(rng),
+            latest_justified: Checkpoint::random(rng),
+            latest_finalized: Checkpoint::random(rng),
+            historical_block_hashes: roots_from_slice(&random_roots(rng, history)).unwrap(),
+            justified_slots: bits_from_slice(&random_bits(rng, history)),
+            validators,
+            justifications_roots: roots_from_slice(&random_roots(rng, pending)).unwrap(),
+            justifications_validators: bits_from_slice(&random_bits(rng, pending * VALIDATOR_REGISTRY_LIMIT)),
+        }
+    }
+}
+
+/// A root made of one repeated byte, so encodings stay readable.
+fn filled(byte: u8) -> Bytes32 {
+    Bytes32(H256([byte; 32]))
+}
+
+fn known_checkpoint(byte: u8, slot: u64) -> Checkpoint {
+    Checkpoint { root: filled(byte), slot: Slot(slot) }
+}
+
+fn known_config() -> ContainerConfig {
+    ContainerConfig { num_validators: 10, genesis_time: 1_609_459_200 }
+}
+
+fn known_pubkey() -> PublicKey {
+    PublicKey { root: filled(0x51), public_seed: filled(0x52) }
+}
+
+fn known_signature() -> Signature {
+    let mut signature = Signature::default();
+    signature.chains.push(filled(0x01)).unwrap();
+    signature.chains.push(filled(0x02)).unwrap();
+    signature.auth_path.push(filled(0x03)).unwrap();
+    signature
+}
+
+fn known_vote() -> Vote {
+    Vote {
+        validator_id: Uint64(3),
+        slot: Slot(9),
+        head: known_checkpoint(0xaa, 9),
+        target: known_checkpoint(0xbb, 8),
+        source: known_checkpoint(0xcc, 4),
+    }
+}
+
+fn known_signed_vote() -> SignedVote {
+    SignedVote { data: known_vote(), signature: known_signature() }
+}
+
+fn known_body() -> BlockBody {
+    let mut attestations = List::default();
+    attestations.push(known_signed_vote()).unwrap();
+    BlockBody { attestations }
+}
+
+fn known_header() -> BlockHeader {
+    BlockHeader {
+        slot: Slot(9),
+        proposer_index: ValidatorIndex(2),
+        parent_root: filled(0x10),
+        state_root: filled(0x20),
+        body_root: filled(0x30),
+    }
+}
+
+fn known_block() -> Block {
+    Block {
+        slot: Slot(9),
+        proposer_index: ValidatorIndex(2),
+        parent_root: filled(0x10),
+        state_root: filled(0x20),
+        body: known_body(),
+    }
+}
+
+fn known_state() -> State {
+    let mut validators = List::default();
+    validators.push(Validator { pubkey: known_pubkey() }).unwrap();
+    validators.push(Validator { pubkey: PublicKey { root: filled(0x61), public_seed: filled(0x62) } }).unwrap();
+    let mut votes = vec![false; VALIDATOR_REGISTRY_LIMIT];
+    votes[0] = true;
+    votes[1] = true;
+    State {
+        config: known_config(),
+        slot: Slot(9),
+        latest_block_header: known_header(),
+        latest_justified: known_checkpoint(0xbb, 8),
+        latest_finalized: known_checkpoint(0xcc, 4),
+        historical_block_hashes: roots_from_slice(&[filled(0xcc), filled(0xdd)]).unwrap(),
+        justified_slots: bits_from_slice(&[true, false, true]),
+        validators,
+        justifications_roots: roots_from_slice(&[filled(0xee)]).unwrap(),
+        justifications_validators: bits_from_slice(&votes),
+    }
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn assert_known_answer<T>(name: &str, value: &T, expected_ssz: &str, expected_root: &str)
+where
+    T: SszReadDefault + SszWrite + SszHash + PartialEq + Debug,
+{
+    let bytes = value.to_ssz().unwrap_or_else(|e| panic!("{}: encoding failed: {:?}", name, e));
+    assert_eq!(to_hex(&bytes), expected_ssz, "{}: serialization differs", name);
+    assert_eq!(&T::from_ssz_default(&bytes).unwrap(), value, "{}: decoded value differs", name);
+    assert_eq!(to_hex(hash_tree_root(value).0.as_bytes()), expected_root, "{}: root differs", name);
+}
+
+fn assert_round_trips<T>(name: &str)
+where
+    T: Random + SszReadDefault + SszWrite + SszHash + PartialEq + Debug,
+{
+    let mut rng = StdRng::seed_from_u64(SEED);
+    for i in 0..ITERATIONS {
+        let value = T::random(&mut rng);
+        let bytes = value.to_ssz().unwrap_or_else(|e| panic!("{} #{}: encoding failed: {:?}", name, i, e));
+        let decoded = T::from_ssz_default(&bytes).unwrap_or_else(|e| panic!("{} #{}: decoding failed: {:?}", name, i, e));
+
+        assert_eq!(decoded, value, "{} #{}: decoded value differs", name, i);
+        assert_eq!(decoded.to_ssz().unwrap(), bytes, "{} #{}: re-encoded bytes differ", name, i);
+        assert_eq!(hash_tree_root(&decoded), hash_tree_root(&value), "{} #{}: root differs", name, i);
+    }
+}
+
+#[test]
+fn test_checkpoint_round_trip() {
+    assert_round_trips::<Checkpoint>("Checkpoint");
+}
+
+#[test]
+fn test_config_round_trip() {
+    assert_round_trips::<ContainerConfig>("Config");
+}
+
+#[test]
+fn test_signature_round_trip() {
+    assert_round_trips::<PublicKey>("PublicKey");
+    assert_round_trips::<Signature>("Signature");
+}
+
+#[test]
+fn test_validator_round_trip() {
+    assert_round_trips::<Validator>("Validator");
+}
+
+#[test]
+fn test_vote_round_trip() {
+    assert_round_trips::<Vote>("Vote");
+    assert_round_trips::<SignedVote>("SignedVote");
+}
+
+#[test]
+fn test_block_round_trip() {
+    assert_round_trips::<BlockBody>("BlockBody");
+    assert_round_trips::<BlockHeader>("BlockHeader");
+    assert_round_trips::<Block>("Block");
+    assert_round_trips::<SignedBlock>("SignedBlock");
+}
+
+#[test]
+fn test_state_round_trip() {
+    assert_round_trips::<State>("State");
+}
+
+#[test]
+fn test_truncated_encoding_is_rejected() {
+    let mut rng = StdRng::seed_from_u64(SEED);
+    let bytes = SignedBlock::random(&mut rng).to_ssz().unwrap();
+    assert!(SignedBlock::from_ssz_default(&bytes[..bytes.len() - 1]).is_err());
+
+    let bytes = Checkpoint::random(&mut rng).to_ssz().unwrap();
+    assert!(Checkpoint::from_ssz_default(&bytes[..bytes.len() - 1]).is_err());
+}
+
+#[test]
+fn test_checkpoint_known_answer() {
+    assert_known_answer(
+        "Checkpoint",
+        &known_checkpoint(0xaa, 9),
+        concat!(
+            "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa",
+            "0900000000000000",
+        ),
+        "4aa9c431766d8089b043f5a231c8dbcbb3de94c5491a4ca7552c5f5f47840f3b",
+    );
+}
+
+#[test]
+fn test_config_known_answer() {
+    assert_known_answer(
+        "Config",
+        &known_config(),
+        "0a000000000000000066ee5f00000000",
+        "bfceb96df5e56ac326093e51adadc84dd20ecc1207d0b236e0ea3dd44f25886c",
+    );
+}
+
+#[test]
+fn test_signature_known_answer() {
+    assert_known_answer(
+        "PublicKey",
+        &known_pubkey(),
+        concat!(
+            "5151515151515151515151515151515151515151515151515151515151515151",
+            "5252525252525252525252525252525252525252525252525252525252525252",
+        ),
+        "cd443445226c26ac34b29b6152c947bcee368ba4493579ddcd887bbc920d274c",
+    );
+    assert_known_answer(
+        "Signature",
+        &known_signature(),
+        concat!(
+            "0800000048000000010101010101010101010101010101010101010101010101",
+            "0101010101010101020202020202020202020202020202020202020202020202",
+            "0202020202020202030303030303030303030303030303030303030303030303",
+            "0303030303030303",
+        ),
+        "9a27b5ba72b3907b3ba2aeca388fbe2c7635b27f4b95c472169f79fa3a3a8034",
+    );
+}
+
+#[test]
+fn test_validator_known_answer() {
+    assert_known_answer(
+        "Validator",
+        &Validator { pubkey: known_pubkey() },
+        concat!(
+            "5151515151515151515151515151515151515151515151515151515151515151",
+            "5252525252525252525252525252525252525252525252525252525252525252",
+        ),
+        "cd443445226c26ac34b29b6152c947bcee368ba4493579ddcd887bbc920d274c",
+    );
+}
+
+#[test]
+fn test_vote_known_answer() {
+    assert_known_answer(
+        "Vote",
+        &known_vote(),
+        concat!(
+            "03000000000000000900000000000000aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa",
+            "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa0900000000000000bbbbbbbbbbbbbbbb",
+            "bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb0800000000000000",
+            "cccccccccccccccccccccccccccccccccccccccccccccccccccccccccccccccc",
+            "0400000000000000",
+        ),
+        "4888f4bd7729215d4bd46df247a199cf84bbb244c1b8752fb7bcde820d6f501c",
+    );
+    assert_known_answer(
+        "SignedVote",
+        &known_signed_vote(),
+        concat!(
+            "03000000000000000900000000000000aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa",
+            "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa0900000000000000bbbbbbbbbbbbbbbb",
+            "bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb0800000000000000",
+            "cccccccccccccccccccccccccccccccccccccccccccccccccccccccccccccccc",
+            "04000000000000008c0000000800000048000000010101010101010101010101",
+            "0101010101010101010101010101010101010101020202020202020202020202",
+            "0202020202020202020202020202020202020202030303030303030303030303",
+            "0303030303030303030303030303030303030303",
+        ),
+        "625dca1311b1bb2f2873f31cfbac2fb3d139017dc02d1989428bd72aa8806af0",
+    );
+}
+
+#[test]
+fn test_block_known_answer() {
+    assert_known_answer(
+        "BlockBody",
+        &known_body(),
+        concat!(
+            "040000000400000003000000000000000900000000000000aaaaaaaaaaaaaaaa",
+            "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa0900000000000000",
+            "bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb",
+            "0800000000000000cccccccccccccccccccccccccccccccccccccccccccccccc",
+            "cccccccccccccccc04000000000000008c000000080000004800000001010101",
+            "0101010101010101010101010101010101010101010101010101010102020202",
+            "0202020202020202020202020202020202020202020202020202020203030303",
+            "03030303030303030303030303030303030303030303030303030303",
+        ),
+        "4cfac9d3fe8c832f1bbdea3ca95fe0f489441d73cd9daa88eb5d1da54ef12463",
+    );
+    assert_known_answer(
+        "BlockHeader",
+        &known_header(),
+        concat!(
+            "0900000000000000020000000000000010101010101010101010101010101010",
+            "1010101010101010101010101010101020202020202020202020202020202020",
+            "2020202020202020202020202020202030303030303030303030303030303030",
+            "30303030303030303030303030303030",
+        ),
+        "25e6161ee1a987560470de60115be25c0a5d520027169529431777b537373a8c",
+    );
+    assert_known_answer(
+        "Block",
+        &known_block(),
+        concat!(
+            "0900000000000000020000000000000010101010101010101010101010101010",
+            "1010101010101010101010101010101020202020202020202020202020202020",
+            "2020202020202020202020202020202054000000040000000400000003000000",
+            "000000000900000000000000aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa",
+            "aaaaaaaaaaaaaaaaaaaaaaaa0900000000000000bbbbbbbbbbbbbbbbbbbbbbbb",
+            "bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb0800000000000000cccccccc",
+            "cccccccccccccccccccccccccccccccccccccccccccccccccccccccc04000000",
+            "000000008c000000080000004800000001010101010101010101010101010101",
+            "0101010101010101010101010101010102020202020202020202020202020202",
+            "0202020202020202020202020202020203030303030303030303030303030303",
+            "03030303030303030303030303030303",
+        ),
+        "04ad3e2b89b3fb50df776f4bf1dae29db5f1d64622fd8e1cd14e742f4dc73c5e",
+    );
+    assert_known_answer(
+        "SignedBlock",
+        &SignedBlock { message: known_block(), signature: known_signature() },
+        concat!(
+            "0800000058010000090000000000000002000000000000001010101010101010",
+            "1010101010101010101010101010101010101010101010102020202020202020",
+            "2020202020202020202020202020202020202020202020205400000004000000",
+            "0400000003000000000000000900000000000000aaaaaaaaaaaaaaaaaaaaaaaa",
+            "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa0900000000000000bbbbbbbb",
+            "bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb08000000",
+            "00000000cccccccccccccccccccccccccccccccccccccccccccccccccccccccc",
+            "cccccccc04000000000000008c00000008000000480000000101010101010101",
+            "0101010101010101010101010101010101010101010101010202020202020202",
+            "0202020202020202020202020202020202020202020202020303030303030303",
+            "0303030303030303030303030303030303030303030303030800000048000000",
+            "0101010101010101010101010101010101010101010101010101010101010101",
+            "0202020202020202020202020202020202020202020202020202020202020202",
+            "0303030303030303030303030303030303030303030303030303030303030303",
+        ),
+        "189eac792d039a26c68f819f8d7ed8da74ae96af2865368c5f7943a5e37f5299",
+    );
+}
+
+#[test]
+fn test_state_known_answer() {
+    // The last field is 4096 vote bits for the single pending root: validators 0 and 1, then the delimiter.
+    let expected = format!(
+        "{}03{}01",
+        concat!(
+            "0a000000000000000066ee5f0000000009000000000000000900000000000000",
+            "0200000000000000101010101010101010101010101010101010101010101010",
+            "1010101010101010202020202020202020202020202020202020202020202020",
+            "2020202020202020303030303030303030303030303030303030303030303030",
+            "3030303030303030bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb",
+            "bbbbbbbbbbbbbbbb0800000000000000cccccccccccccccccccccccccccccccc",
+            "cccccccccccccccccccccccccccccccc0400000000000000ec0000002c010000",
+            "2d010000ad010000cd010000cccccccccccccccccccccccccccccccccccccccc",
+            "ccccccccccccccccccccccccdddddddddddddddddddddddddddddddddddddddd",
+            "dddddddddddddddddddddddd0d51515151515151515151515151515151515151",
+            "5151515151515151515151515152525252525252525252525252525252525252",
+            "5252525252525252525252525261616161616161616161616161616161616161",
+            "6161616161616161616161616162626262626262626262626262626262626262",
+            "62626262626262626262626262eeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeee",
+            "eeeeeeeeeeeeeeeeeeeeeeeeee",
+        ),
+        "00".repeat(511),
+    );
+    assert_known_answer(
+        "State",
+        &known_state(),
+        &expected,
+        "bf091efe869df758b551779f4ef160ab889be14ccd16b75308225c4cbc199e8d",
+    );
+}
@@ -1,42 +1,55 @@
 // tests/state_basic.rs
-use containers::{block::{BlockBody, hash_tree_root}, state::State, types::Uint64, ValidatorIndex};
+use containers::{block::{BlockBody, hash_tree_root}, state::{bits_to_vec, roots_to_vec, State}, types::Uint64, Checkpoint, PublicKey, Slot, StateTransitionError, ValidatorIndex};
 use pretty_assertions::assert_eq;
 
 #[path = "common.rs"]
 mod common;
-use common::sample_config;
+use common::{genesis_state, sample_config, test_pubkeys};
 
 #[test]
 fn test_generate_genesis() {
     let config = sample_config();
-    let state = State::generate_genesis(Uint64(config.genesis_time), Uint64(config.num_validators));
+    let state = genesis_state();
 
     assert_eq!(state.config, config);
+    assert_eq!(state.validators.len_usize(), test_pubkeys().len());
+    for (index, pubkey) in test_pubkeys().iter().enumerate() {
+        assert_eq!(state.validator_pubkey(ValidatorIndex(index as u64)), Ok(pubkey));
+    }
+    assert_eq!(state.validator(ValidatorIndex(10)), Err(StateTransitionError::UnknownValidator(ValidatorIndex(10))));
     assert_eq!(state.slot.0, 0);
 
     let empty_body = BlockBody { attestations: ssz::PersistentList::default() };
     assert_eq!(state.latest_block_header.body_root, hash_tree_root(&empty_body));
 
     
-    assert!(state.historical_block_hashes.is_empty());
-    assert!(state.justified_slots.is_empty());
-    assert!(state.justifications_roots.is_empty());
-    assert!(state.justifications_validators.is_empty());
+    assert!(roots_to_vec(&state.historical_block_hashes).is_empty());
+    assert!(bits_to_vec(&state.justified_slots).is_empty());
+    assert!(roots_to_vec(&state.justifications_roots).is_empty());
+    assert!(bits_to_vec(&state.justifications_validators).is_empty());
 }
 
 #[test]
 fn test_proposer_round_robin() {
-    let state = State::generate_genesis(Uint64(0), Uint64(4));
+    let state = State::generate_genesis(Uint64(0), &vec![PublicKey::default(); 4]);
     assert!(state.is_proposer(containers::types::ValidatorIndex(0)));
+
+    let empty = State::generate_genesis(Uint64(0), &[]);
+    assert!(!empty.is_proposer(containers::types::ValidatorIndex(0)));
 }
 
 #[test]
 fn test_slot_justifiability_rules() {
-    use containers::slot::Slot;
 
     assert!(Slot(1).is_justifiable_after(Slot(0)));
     assert!(Slot(9).is_justifiable_after(Slot(0))); // perfect square
     assert!(Slot(6).is_justifiable_after(Slot(0))); // pronic (2*3)
+    assert!(!Slot(7).is_justifiable_after(Slot(0)));
+    // Exact at the top of the range, where f64 square roots round
+    assert!(Slot(4294967295 * 4294967296).is_justifiable_after(Slot(0))); // largest pronic
+    assert!(!Slot(4294967295 * 4294967295 + 1).is_justifiable_after(Slot(0)));
+    assert!(!Slot(u64::MAX).is_justifiable_after(Slot(0)));
+    assert!(!Slot(0).is_justifiable_after(Slot(1))); // before finalized
 }
 
 #[test]
@@ -52,4 +65,28 @@ fn test_hash_tree_root() {
 
     let root = hash_tree_root(&block);
     assert_ne!(root, containers::types::Bytes32(ssz::H256::zero()));
-}
\ No newline at end of file
+}
+#[test]
+fn test_state_root_commits_to_validators() {
+    let state = genesis_state();
+    let mut swapped = test_pubkeys();
+    swapped.swap(0, 1);
+    let other = State::generate_genesis(Uint64(sample_config().genesis_time), &swapped);
+
+    assert_eq!(other.config, state.config);
+    assert_ne!(hash_tree_root(&other), hash_tree_root(&state));
+}
+
+#[test]
+fn test_genesis_block_and_anchor_checkpoint() {
+    let state = genesis_state();
+    let block = state.genesis_block();
+    let anchor = state.anchor_checkpoint();
+
+    assert_eq!(block.slot, Slot(0));
+    assert_eq!(block.state_root, hash_tree_root(&state));
+    assert_eq!(anchor, Checkpoint { root: hash_tree_root(&block), slot: Slot(0) });
+    // process_slot caches the same state root in the header, so the anchor doesn't move
+    assert_eq!(state.process_slot().anchor_checkpoint(), anchor);
+    assert_eq!(hash_tree_root(&state.process_slot().latest_block_header), anchor.root);
+}
@@ -1,12 +1,12 @@
 // tests/state_justifications.rs
 use containers::{
-    state::State,
+    block::hash_tree_root,
+    state::{bits_from_slice, bits_to_vec, roots_from_slice, roots_to_vec, State, StateTransitionError},
     types::Bytes32,
     ContainerConfig
 };
 use pretty_assertions::assert_eq;
 use rstest::{fixture, rstest};
-use ssz::PersistentList as List;
 
 #[path = "common.rs"]
 mod common;
@@ -28,10 +28,10 @@ fn state(config: ContainerConfig) -> State {
 fn test_get_justifications_empty() {
     let state = state(sample_config());
 
-    assert!(state.justifications_roots.is_empty());
-    assert!(state.justifications_validators.is_empty());
+    assert!(roots_to_vec(&state.justifications_roots).is_empty());
+    assert!(bits_to_vec(&state.justifications_validators).is_empty());
 
-    let justifications = state.get_justifications();
+    let justifications = state.get_justifications().unwrap();
     assert!(justifications.is_empty());
 }
 
@@ -44,10 +44,10 @@ fn test_get_justifications_single_root() {
     votes1[2] = true;
     votes1[5] = true;
 
-    state.justifications_roots = vec![root1];
-    state.justifications_validators = votes1.clone();
+    state.justifications_roots = roots_from_slice(&[root1]).unwrap();
+    state.justifications_validators = bits_from_slice(&votes1);
 
-    let justifications = state.get_justifications();
+    let justifications = state.get_justifications().unwrap();
 
     let expected = vec![(root1, votes1)].into_iter().collect();
     assert_eq!(justifications, expected);
@@ -73,10 +73,10 @@ fn test_get_justifications_multiple_roots() {
 
     let all_votes = [votes1.clone(), votes2.clone(), votes3.clone()].concat();
 
-    state.justifications_roots = vec![root1, root2, root3];
-    state.justifications_validators = all_votes;
+    state.justifications_roots = roots_from_slice(&[root1, root2, root3]).unwrap();
+    state.justifications_validators = bits_from_slice(&all_votes);
 
-    let justifications = state.get_justifications();
+    let justifications = state.get_justifications().unwrap();
 
     let mut expected = std::collections::BTreeMap::new();
     expected.insert(root1, votes1);
@@ -87,20 +87,37 @@ fn test_get_justifications_multiple_roots() {
     assert_eq!(justifications, expected);
 }
 
+#[rstest]
+#[case::too_few_bits(2, DEVNET_CONFIG_VALIDATOR_REGISTRY_LIMIT + 1)]
+#[case::too_many_bits(1, DEVNET_CONFIG_VALIDATOR_REGISTRY_LIMIT + 1)]
+#[case::bits_without_roots(0, 8)]
+fn test_get_justifications_mismatched_length(#[case] roots: u8, #[case] len: usize) {
+    let mut state = state(sample_config());
+    let roots: Vec<_> = (1..=roots).map(|i| Bytes32(ssz::H256::from_slice(&[i; 32]))).collect();
+
+    state.justifications_roots = roots_from_slice(&roots).unwrap();
+    state.justifications_validators = bits_from_slice(&vec![true; len]);
+
+    assert_eq!(
+        state.get_justifications(),
+        Err(StateTransitionError::JustificationValidatorsLength { roots: roots.len(), len })
+    );
+}
+
 #[test]
 fn test_with_justifications_empty() {
     let config = sample_config();
     let mut initial_state = base_state(config.clone());
 
-    initial_state.justifications_roots = vec![Bytes32(ssz::H256::from_slice(&[1u8;32]))];
-    initial_state.justifications_validators = vec![true; DEVNET_CONFIG_VALIDATOR_REGISTRY_LIMIT];
+    initial_state.justifications_roots = roots_from_slice(&[Bytes32(ssz::H256::from_slice(&[1u8;32]))]).unwrap();
+    initial_state.justifications_validators = bits_from_slice(&[true; DEVNET_CONFIG_VALIDATOR_REGISTRY_LIMIT]);
 
-    let new_state = initial_state.clone().with_justifications(std::collections::BTreeMap::new());
+    let new_state = initial_state.clone().with_justifications(std::collections::BTreeMap::new()).unwrap();
 
-    assert!(new_state.justifications_roots.is_empty());
-    assert!(new_state.justifications_validators.is_empty());
-    assert!(!initial_state.justifications_roots.is_empty());
-    assert!(!initial_state.justifications_validators.is_empty());
+    assert!(roots_to_vec(&new_state.justifications_roots).is_empty());
+    assert!(bits_to_vec(&new_state.justifications_validators).is_empty());
+    assert!(!roots_to_vec(&initial_state.justifications_roots).is_empty());
+    assert!(!bits_to_vec(&initial_state.justifications_validators).is_empty());
 }
 
 #[test]
@@ -117,17 +134,66 @@ fn test_with_justifications_deterministic_order() {
     justifications.insert(root2, votes2.clone());
     justifications.insert(root1, votes1.clone());
 
-    let new_state = state.with_justifications(justifications);
+    let new_state = state.with_justifications(justifications).unwrap();
 
     let expected_roots = vec![root1, root2];
     let expected_validators = [votes1, votes2].concat();
 
-    assert_eq!(new_state.justifications_roots, expected_roots);
-    assert_eq!(new_state.justifications_validators, expected_validators);
+    assert_eq!(roots_to_vec(&new_state.justifications_roots), expected_roots);
+    assert_eq!(bits_to_vec(&new_state.justifications_validators), expected_validators);
+}
+
+#[test]
+fn test_state_root_commits_to_pending_justifications() {
+    let state = state(sample_config());
+    let root1 = Bytes32(ssz::H256::from_slice(&[1u8; 32]));
+
+    let mut with_one_vote = std::collections::BTreeMap::new();
+    with_one_vote.insert(root1, create_votes(&[0]));
+    let mut with_two_votes = std::collections::BTreeMap::new();
+    with_two_votes.insert(root1, create_votes(&[0, 1]));
+
+    let one_vote_state = state.clone().with_justifications(with_one_vote).unwrap();
+    let two_vote_state = state.clone().with_justifications(with_two_votes).unwrap();
+
+    assert_ne!(hash_tree_root(&state), hash_tree_root(&one_vote_state));
+    assert_ne!(hash_tree_root(&one_vote_state), hash_tree_root(&two_vote_state));
+}
+
+#[test]
+fn test_state_root_commits_to_history() {
+    let state = state(sample_config());
+    let root1 = Bytes32(ssz::H256::from_slice(&[1u8; 32]));
+
+    let mut with_history = state.clone().with_historical_hashes(vec![root1]).unwrap();
+    assert_ne!(hash_tree_root(&state), hash_tree_root(&with_history));
+
+    let before_justified = hash_tree_root(&with_history);
+    with_history.justified_slots = bits_from_slice(&[true]);
+    assert_ne!(before_justified, hash_tree_root(&with_history));
+}
+
+#[test]
+fn test_state_serde_roundtrip_keeps_justifications() {
+    let root1 = Bytes32(ssz::H256::from_slice(&[1u8; 32]));
+    let mut justifications = std::collections::BTreeMap::new();
+    justifications.insert(root1, create_votes(&[3, 4]));
+
+    let mut state = state(sample_config())
+        .with_historical_hashes(vec![root1])
+        .unwrap()
+        .with_justifications(justifications)
+        .unwrap();
+    state.justified_slots = bits_from_slice(&[true, false]);
+
+    let json = serde_json::to_string(&state).unwrap();
+    let decoded: State = serde_json::from_str(&json).unwrap();
+
+    assert_eq!(decoded, state);
+    assert_eq!(hash_tree_root(&decoded), hash_tree_root(&state));
 }
 
 #[test]
-#[should_panic(expected = "vote vector must match validator limit")]
 fn test_with_justifications_invalid_length() {
     let state = state(sample_config());
     let root1 = Bytes32(ssz::H256::from_slice(&[1u8; 32]));
@@ -136,7 +202,22 @@ fn test_with_justifications_invalid_length() {
     let mut justifications = std::collections::BTreeMap::new();
     justifications.insert(root1, invalid_votes);
 
-    let _ = state.with_justifications(justifications);
+    assert_eq!(
+        state.with_justifications(justifications),
+        Err(StateTransitionError::JustificationVotesLength { root: root1, len: DEVNET_CONFIG_VALIDATOR_REGISTRY_LIMIT - 1 })
+    );
+}
+
+#[test]
+fn test_roots_from_slice_reports_the_list_limit() {
+    let roots = [Bytes32(ssz::H256::from_slice(&[1u8; 32])); 3];
+
+    let list: ssz::PersistentList<Bytes32, typenum::U3> = roots_from_slice(&roots).unwrap();
+    assert_eq!(roots_to_vec(&list), roots.to_vec());
+    assert_eq!(
+        roots_from_slice::<typenum::U2>(&roots),
+        Err(StateTransitionError::RootsLimitExceeded { len: 3, limit: 2 })
+    );
 }
 
 #[rstest]
@@ -170,8 +251,8 @@ fn test_justifications_roundtrip(
 ) {
     let state = state(sample_config());
 
-    let new_state = state.with_justifications(justifications_map.clone());
-    let reconstructed_map = new_state.get_justifications();
+    let new_state = state.with_justifications(justifications_map.clone()).unwrap();
+    let reconstructed_map = new_state.get_justifications().unwrap();
 
     let expected_map = justifications_map;
     // BTreeMap is already ordered by key; direct comparison is deterministic
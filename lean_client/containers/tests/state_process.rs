@@ -2,8 +2,9 @@
 use containers::{
     block::{Block, BlockBody, hash_tree_root},
     checkpoint::Checkpoint,
+    signature::Signature,
     slot::Slot,
-    state::State,
+    state::{bits_to_vec, roots_to_vec, State, StateTransitionError},
     types::{Bytes32, Uint64, ValidatorIndex},
     vote::{SignedVote, Vote},
 };
@@ -14,12 +15,11 @@ use typenum::U4096;
 
 #[path = "common.rs"]
 mod common;
-use common::{create_block, sample_config};
+use common::create_block;
 
 #[fixture]
 pub fn genesis_state() -> State {
-    let config = sample_config();
-    State::generate_genesis(Uint64(config.genesis_time), Uint64(config.num_validators))
+    common::genesis_state()
 }
 
 #[test]
@@ -42,50 +42,55 @@ fn test_process_slots() {
     let genesis_state = genesis_state();
     let target_slot = Slot(5);
 
-    let new_state = genesis_state.process_slots(target_slot);
+    let new_state = genesis_state.process_slots(target_slot).unwrap();
 
     assert_eq!(new_state.slot, target_slot);
     assert_eq!(new_state.latest_block_header.state_root, hash_tree_root(&genesis_state));
 }
 
 #[test]
-#[should_panic]
 fn test_process_slots_backwards() {
     let genesis_state = genesis_state();
-    let advanced_state = genesis_state.process_slots(Slot(5));
+    let advanced_state = genesis_state.process_slots(Slot(5)).unwrap();
 
-    let _ = advanced_state.process_slots(Slot(4)); // Should panic
+    assert_eq!(
+        advanced_state.process_slots(Slot(4)),
+        Err(StateTransitionError::TargetSlotNotInFuture { current: Slot(5), target: Slot(4) })
+    );
 }
 
 #[test]
 fn test_process_block_header_valid() {
     let genesis_state = genesis_state();
-    let mut state_at_slot_1 = genesis_state.process_slots(Slot(1));
+    let mut state_at_slot_1 = genesis_state.process_slots(Slot(1)).unwrap();
     let genesis_header_root = hash_tree_root(&state_at_slot_1.latest_block_header);
 
     let block = create_block(1, &mut state_at_slot_1.latest_block_header, None).message;
-    let new_state = state_at_slot_1.process_block_header(&block);
+    let new_state = state_at_slot_1.process_block_header(&block).unwrap();
 
     assert_eq!(new_state.latest_finalized.root, genesis_header_root);
     assert_eq!(new_state.latest_justified.root, genesis_header_root);
-    assert_eq!(new_state.historical_block_hashes.as_slice(), &[genesis_header_root]);
-    assert_eq!(new_state.justified_slots.as_slice(), &[true]);
+    assert_eq!(roots_to_vec(&new_state.historical_block_hashes), vec![genesis_header_root]);
+    assert_eq!(bits_to_vec(&new_state.justified_slots), vec![true]);
     assert_eq!(new_state.latest_block_header.slot, Slot(1));
     assert_eq!(new_state.latest_block_header.state_root, Bytes32(ssz::H256::zero()));
 }
 
 #[rstest]
-#[case(2, 1, None, "Block slot mismatch")]
-#[case(1, 2, None, "Incorrect block proposer")]
-#[case(1, 1, Some(Bytes32(ssz::H256::from_slice(&[0xde; 32]))), "Block parent root mismatch")]
+#[case::slot_mismatch(2, 1, None, |_| StateTransitionError::SlotMismatch { block_slot: Slot(2), state_slot: Slot(1) })]
+#[case::incorrect_proposer(1, 2, None, |_| StateTransitionError::IncorrectProposer { slot: Slot(1), proposer_index: ValidatorIndex(2) })]
+#[case::parent_root_mismatch(1, 1, Some(Bytes32(ssz::H256::from_slice(&[0xde; 32]))), |parent_root| StateTransitionError::ParentRootMismatch {
+    expected: parent_root,
+    actual: Bytes32(ssz::H256::from_slice(&[0xde; 32])),
+})]
 fn test_process_block_header_invalid(
     #[case] bad_slot: u64,
     #[case] bad_proposer: u64,
     #[case] bad_parent_root: Option<Bytes32>,
-    #[case] expected_error: &str,
+    #[case] expected_error: fn(Bytes32) -> StateTransitionError,
 ) {
     let genesis_state = genesis_state();
-    let state_at_slot_1 = genesis_state.process_slots(Slot(1));
+    let state_at_slot_1 = genesis_state.process_slots(Slot(1)).unwrap();
     let parent_header = &state_at_slot_1.latest_block_header;
     let parent_root = hash_tree_root(parent_header);
 
@@ -97,13 +102,25 @@ fn test_process_block_header_invalid(
         body: BlockBody { attestations: List::default() },
     };
 
-    let result = std::panic::catch_unwind(|| {
-        state_at_slot_1.process_block_header(&block);
-    });
+    let result = state_at_slot_1.process_block_header(&block);
 
-    assert!(result.is_err());
-    let panic_msg = result.unwrap_err().downcast::<String>().unwrap();
-    assert!(panic_msg.contains(expected_error));
+    assert_eq!(result, Err(expected_error(parent_root)));
+}
+
+#[test]
+fn test_process_block_header_older_than_latest_header() {
+    let genesis_state = genesis_state();
+    let mut state_at_slot_1 = genesis_state.process_slots(Slot(1)).unwrap();
+    let block1 = create_block(1, &mut state_at_slot_1.latest_block_header, None);
+    let state = state_at_slot_1.process_block(&block1.message).unwrap();
+
+    let mut replayed = block1.message.clone();
+    replayed.parent_root = hash_tree_root(&state.latest_block_header);
+
+    assert_eq!(
+        state.process_block_header(&replayed),
+        Err(StateTransitionError::BlockOlderThanLatestHeader { block_slot: Slot(1), header_slot: Slot(1) })
+    );
 }
 
 #[test]
@@ -111,20 +128,20 @@ fn test_process_attestations_justification_and_finalization() {
     let mut state = genesis_state();
 
     // Process slot 1 and block
-    let mut state_at_slot_1 = state.process_slots(Slot(1));
+    let mut state_at_slot_1 = state.process_slots(Slot(1)).unwrap();
     let block1 = create_block(1, &mut state_at_slot_1.latest_block_header, None);
-    state = state_at_slot_1.process_block(&block1.message);
+    state = state_at_slot_1.process_block(&block1.message).unwrap();
 
     // Process slot 4 and block
-    let mut state_at_slot_4 = state.process_slots(Slot(4));
+    let mut state_at_slot_4 = state.process_slots(Slot(4)).unwrap();
     let block4 = create_block(4, &mut state_at_slot_4.latest_block_header, None);
-    state = state_at_slot_4.process_block(&block4.message);
+    state = state_at_slot_4.process_block(&block4.message).unwrap();
 
     // Advance to slot 5
-    state = state.process_slots(Slot(5));
+    state = state.process_slots(Slot(5)).unwrap();
 
     let genesis_checkpoint = Checkpoint {
-        root: state.historical_block_hashes[0],
+        root: roots_to_vec(&state.historical_block_hashes)[0],
         slot: Slot(0),
     };
 
@@ -142,7 +159,7 @@ fn test_process_attestations_justification_and_finalization() {
                 target: checkpoint4.clone(),
                 source: genesis_checkpoint.clone(),
             },
-            signature: Bytes32(ssz::H256::zero()),
+            signature: Signature::default(),
         })
         .collect();
 
@@ -150,10 +167,46 @@ fn test_process_attestations_justification_and_finalization() {
     let mut votes_list: List<_, U4096> = List::default();
     for v in votes_for_4 { votes_list.push(v).unwrap(); }
 
-    let new_state = state.process_attestations(&votes_list);
+    let new_state = state.process_attestations(&votes_list).unwrap();
 
     assert_eq!(new_state.latest_justified, checkpoint4);
-    assert!(new_state.justified_slots[4]);
+    assert!(bits_to_vec(&new_state.justified_slots)[4]);
     assert_eq!(new_state.latest_finalized, genesis_checkpoint);
-    assert!(!new_state.get_justifications().contains_key(&checkpoint4.root));
-}
\ No newline at end of file
+    assert!(!new_state.get_justifications().unwrap().contains_key(&checkpoint4.root));
+}
+#[test]
+fn test_process_attestations_after_finality_moved_within_the_block() {
+    let mut state = genesis_state();
+    for slot in 1..=4 {
+        let mut state_at_slot = state.process_slots(Slot(slot)).unwrap();
+        let block = create_block(slot, &mut state_at_slot.latest_block_header, None);
+        state = state_at_slot.process_block(&block.message).unwrap();
+    }
+    state = state.process_slots(Slot(5)).unwrap();
+
+    let history = roots_to_vec(&state.historical_block_hashes);
+    let checkpoint = |slot: u64| Checkpoint {
+        root: history.get(slot as usize).copied().unwrap_or_else(|| hash_tree_root(&state.latest_block_header)),
+        slot: Slot(slot),
+    };
+
+    // 0->1, 1->2 and 2->3 finalize slot 2, after which 0->4 spans slots before the finalized one
+    let mut votes_list: List<_, U4096> = List::default();
+    for (source, target) in [(0, 1), (1, 2), (2, 3), (0, 4)] {
+        for i in 0..7 {
+            let vote = Vote {
+                validator_id: Uint64(i),
+                slot: Slot(target),
+                head: checkpoint(target),
+                target: checkpoint(target),
+                source: checkpoint(source),
+            };
+            votes_list.push(SignedVote { data: vote, signature: Signature::default() }).unwrap();
+        }
+    }
+
+    let new_state = state.process_attestations(&votes_list).unwrap();
+
+    assert_eq!(new_state.latest_justified, checkpoint(4));
+    assert_eq!(new_state.latest_finalized, checkpoint(2));
+}
@@ -1,82 +1,98 @@
 // tests/state_transition.rs
-use containers::{block::{Block, SignedBlock, hash_tree_root}, state::State, types::{Bytes32, Uint64}, Slot};
+use containers::{
+    block::{Block, SignedBlock, hash_tree_root},
+    signature::Signature,
+    state::{State, StateTransitionError},
+    types::{Bytes32, Uint64, ValidatorIndex},
+    vote::Vote,
+    Checkpoint, Slot,
+};
 use pretty_assertions::assert_eq;
 use rstest::fixture;
+use ssz::PersistentList as List;
 
 #[path = "common.rs"]
 mod common;
-use common::{create_block, sample_config};
+use common::{create_block, sign_block, sign_vote};
 
 #[fixture]
 fn genesis_state() -> State {
-    let config = sample_config();
-    State::generate_genesis(Uint64(config.genesis_time), Uint64(config.num_validators))
+    common::genesis_state()
+}
+
+/// The slot 1 block on top of genesis with its state root filled in, unsigned.
+fn block_at_slot_1(state: &State, votes: Option<List<containers::SignedVote, typenum::U4096>>) -> (Block, State) {
+    let mut state_at_slot_1 = state.process_slots(Slot(1)).unwrap();
+    let block = create_block(1, &mut state_at_slot_1.latest_block_header, votes).message;
+    let expected_state = state_at_slot_1.process_block(&block).unwrap();
+    (Block { state_root: hash_tree_root(&expected_state), ..block }, expected_state)
 }
 
 #[test]
 fn test_state_transition_full() {
     let state = genesis_state();
-    let mut state_at_slot_1 = state.process_slots(Slot(1));
-
-    let signed_block = create_block(1, &mut state_at_slot_1.latest_block_header, None);
-    let block = signed_block.message.clone();
-
-    let expected_state = state_at_slot_1.process_block(&block.clone());
+    let (block, expected_state) = block_at_slot_1(&state, None);
 
-    let block_with_correct_root = Block {
-    state_root: hash_tree_root(&expected_state),
-        ..block
-    };
-
-    let final_signed_block = SignedBlock {
-        message: block_with_correct_root,
-        signature: signed_block.signature,
-    };
-
-    let final_state = state.state_transition(final_signed_block, true);
+    let final_state = state.state_transition(sign_block(block)).unwrap();
 
     assert_eq!(final_state, expected_state);
+    assert_eq!(final_state.validators, state.validators);
 }
 
 #[test]
-#[should_panic(expected = "Block signatures must be valid")]
 fn test_state_transition_invalid_signatures() {
     let state = genesis_state();
-    let mut state_at_slot_1 = state.process_slots(Slot(1));
+    let (block, _) = block_at_slot_1(&state, None);
 
-    let signed_block = create_block(1, &mut state_at_slot_1.latest_block_header, None);
-    let block = signed_block.message.clone();
+    let unsigned = SignedBlock { message: block.clone(), signature: Signature::default() };
+    let invalid_block_signature = Err(StateTransitionError::InvalidBlockSignature { proposer_index: ValidatorIndex(1), slot: Slot(1) });
+    assert_eq!(state.state_transition(unsigned), invalid_block_signature);
 
-    let expected_state = state_at_slot_1.process_block(&block.clone());
+    // A valid signature over a different message doesn't carry over
+    let mut tampered = sign_block(block);
+    tampered.message.state_root = Bytes32(ssz::H256::zero());
+    assert_eq!(state.state_transition(tampered), invalid_block_signature);
+}
 
-    let block_with_correct_root = Block {
-    state_root: hash_tree_root(&expected_state),
-        ..block
-    };
+#[test]
+fn test_state_transition_checks_vote_signatures() {
+    let state = genesis_state();
+    let genesis = Checkpoint { root: Bytes32(ssz::H256::zero()), slot: Slot(0) };
+    let vote = Vote { validator_id: Uint64(2), slot: Slot(0), head: genesis.clone(), target: genesis.clone(), source: genesis };
+
+    let mut forged = sign_vote(vote.clone());
+    forged.data.validator_id = Uint64(3);
+    let mut votes = List::default();
+    votes.push(sign_vote(vote)).unwrap();
+    votes.push(forged).unwrap();
+    let (block, _) = block_at_slot_1(&state, Some(votes));
+
+    assert_eq!(
+        state.state_transition(sign_block(block)),
+        Err(StateTransitionError::InvalidVoteSignature { validator_id: ValidatorIndex(3), slot: Slot(0) })
+    );
+}
 
-    let final_signed_block = SignedBlock {
-        message: block_with_correct_root,
-        signature: signed_block.signature,
-    };
+#[test]
+fn test_state_transition_rejects_unregistered_proposer() {
+    let mut state = genesis_state();
+    state.validators = List::default();
+    let (block, _) = block_at_slot_1(&state, None);
 
-    state.state_transition(final_signed_block, false);
+    assert_eq!(state.state_transition(sign_block(block)), Err(StateTransitionError::UnknownValidator(ValidatorIndex(1))));
 }
 
 #[test]
-#[should_panic(expected = "Invalid block state root")]
 fn test_state_transition_bad_state_root() {
     let state = genesis_state();
-    let mut state_at_slot_1 = state.process_slots(Slot(1));
-
-    let signed_block = create_block(1, &mut state_at_slot_1.latest_block_header, None);
-    let mut block = signed_block.message.clone();
+    let (mut block, _) = block_at_slot_1(&state, None);
 
     block.state_root = Bytes32(ssz::H256::zero());
 
-    let final_signed_block = SignedBlock {
-        message: block,
-        signature: signed_block.signature,
-    };
+    let result = state.state_transition(sign_block(block));
 
-    state.state_transition(final_signed_block, true);
-}
\ No newline at end of file
+    assert!(matches!(
+        result,
+        Err(StateTransitionError::StateRootMismatch { expected, .. }) if expected == Bytes32(ssz::H256::zero())
+    ));
+}
@@ -0,0 +1,103 @@
+use crate::store::supermajority_threshold;
+use containers::{Checkpoint, SignedVote, Slot, State, ValidatorIndex};
+use std::collections::{BTreeMap, HashMap};
+
+/// Signed votes waiting to be packed into blocks, grouped by the checkpoint they try to justify.
+#[derive(Clone, Debug, Default)]
+pub struct AttestationPool {
+    votes: HashMap<Checkpoint, BTreeMap<ValidatorIndex, SignedVote>>,
+    /// Justified slot of the state last pruned against; older sources are no longer packed.
+    justified_slot: Slot,
+}
+
+impl AttestationPool {
+    pub fn len(&self) -> usize {
+        self.votes.values().map(BTreeMap::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.votes.is_empty()
+    }
+
+    /// Keeps one vote per validator and target, the one for the latest slot.
+    /// Returns false if the pool already had that vote or a newer one, or if its source is
+    /// older than the justified checkpoint of the state the pool was last pruned against.
+    pub fn insert(&mut self, signed_vote: SignedVote) -> bool {
+        if signed_vote.data.source.slot < self.justified_slot {
+            return false;
+        }
+        let validator = ValidatorIndex(signed_vote.data.validator_id.0);
+        let votes = self.votes.entry(signed_vote.data.target.clone()).or_default();
+        if votes.get(&validator).is_some_and(|pooled| pooled.data.slot >= signed_vote.data.slot) {
+            return false;
+        }
+        votes.insert(validator, signed_vote);
+        true
+    }
+
+    pub fn targets(&self) -> impl Iterator<Item = &Checkpoint> {
+        self.votes.keys()
+    }
+
+    /// Votes for `target` in validator order.
+    pub fn votes_for(&self, target: &Checkpoint) -> impl Iterator<Item = &SignedVote> {
+        self.votes.get(target).into_iter().flat_map(BTreeMap::values)
+    }
+
+    pub fn remove_validator(&mut self, validator: &ValidatorIndex) {
+        self.retain(|signed_vote| signed_vote.data.validator_id.0 != validator.0);
+    }
+
+    pub fn retain(&mut self, mut keep: impl FnMut(&SignedVote) -> bool) {
+        for votes in self.votes.values_mut() {
+            votes.retain(|_, signed_vote| keep(signed_vote));
+        }
+        self.votes.retain(|_, votes| !votes.is_empty());
+    }
+
+    /// Drops every vote `process_attestations` would reject in `state`, e.g. one with an
+    /// unjustified source or a target that is already justified or can no longer be, and
+    /// every vote sourced before `state`'s justified checkpoint. Later inserts of such votes
+    /// are refused.
+    pub fn prune(&mut self, state: &State) {
+        self.justified_slot = self.justified_slot.max(state.latest_justified.slot);
+        let justified_slot = self.justified_slot;
+        let check = state.vote_check();
+        self.retain(|signed_vote| signed_vote.data.source.slot >= justified_slot && check.is_valid(&signed_vote.data));
+    }
+
+    /// Up to `limit` votes that would count in `state`, for a block built on it.
+    ///
+    /// Targets that end up closest to the 2/3 threshold come first, so a full block still
+    /// justifies as much as it can. Votes `state` already counted for their target are skipped.
+    pub fn select(&self, state: &State, limit: usize) -> Vec<SignedVote> {
+        // A state whose vote bits don't match its roots can't take a block, so offer nothing.
+        let Ok(justifications) = state.get_justifications() else {
+            return Vec::new();
+        };
+        let threshold = supermajority_threshold(state.config.num_validators);
+        let check = state.vote_check();
+
+        let mut candidates = Vec::new();
+        for (target, votes) in &self.votes {
+            let counted = justifications.get(&target.root);
+            let is_counted = |validator: &ValidatorIndex| {
+                counted.and_then(|bits| bits.get(validator.0 as usize)).copied().unwrap_or(false)
+            };
+            let fresh: Vec<&SignedVote> = votes
+                .iter()
+                .filter(|(validator, signed_vote)| !is_counted(validator) && check.is_valid(&signed_vote.data))
+                .map(|(_, signed_vote)| signed_vote)
+                .collect();
+            if fresh.is_empty() {
+                continue;
+            }
+            let already_counted = counted.map_or(0, |bits| bits.iter().filter(|&&bit| bit).count());
+            let missing = threshold.saturating_sub(already_counted + fresh.len());
+            candidates.push(((missing, target.slot, target.root), fresh));
+        }
+        candidates.sort_by_key(|(key, _)| *key);
+
+        candidates.into_iter().flat_map(|(_, votes)| votes).take(limit).cloned().collect()
+    }
+}
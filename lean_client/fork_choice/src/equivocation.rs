@@ -0,0 +1,88 @@
+use containers::{block::hash_tree_root, SignedBlock, SignedVote, Slot, ValidatorIndex};
+use std::collections::{HashMap, HashSet};
+
+/// Two conflicting messages signed by the same validator for the same slot, in the order they were seen.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EquivocationReport {
+    Block { first: SignedBlock, second: SignedBlock },
+    Vote { first: SignedVote, second: SignedVote },
+}
+
+impl EquivocationReport {
+    pub fn validator(&self) -> ValidatorIndex {
+        match self {
+            Self::Block { first, .. } => first.message.proposer_index,
+            Self::Vote { first, .. } => ValidatorIndex(first.data.validator_id.0),
+        }
+    }
+
+    pub fn slot(&self) -> Slot {
+        match self {
+            Self::Block { first, .. } => first.message.slot,
+            Self::Vote { first, .. } => first.data.slot,
+        }
+    }
+}
+
+/// Remembers the first block and vote seen from each validator per slot and reports any conflicting second one.
+#[derive(Clone, Debug, Default)]
+pub struct EquivocationDetector {
+    blocks: HashMap<(ValidatorIndex, Slot), SignedBlock>,
+    votes: HashMap<(ValidatorIndex, Slot), SignedVote>,
+    equivocators: HashSet<ValidatorIndex>,
+    reports: Vec<EquivocationReport>,
+}
+
+impl EquivocationDetector {
+    /// Records the block; returns a report if its proposer already signed a different block for the slot.
+    pub fn observe_block(&mut self, signed_block: &SignedBlock) -> Option<EquivocationReport> {
+        let key = (signed_block.message.proposer_index, signed_block.message.slot);
+        let first = self.blocks.entry(key).or_insert_with(|| signed_block.clone());
+        if hash_tree_root(&first.message) == hash_tree_root(&signed_block.message) {
+            return None;
+        }
+        let report = EquivocationReport::Block { first: first.clone(), second: signed_block.clone() };
+        Some(self.record(report))
+    }
+
+    /// Records the vote; returns a report if its validator already signed a different vote for the slot.
+    /// The same vote seen again, e.g. once over gossip and once in a block, is not an equivocation.
+    pub fn observe_vote(&mut self, signed_vote: &SignedVote) -> Option<EquivocationReport> {
+        let key = (ValidatorIndex(signed_vote.data.validator_id.0), signed_vote.data.slot);
+        let first = self.votes.entry(key).or_insert_with(|| signed_vote.clone());
+        if first.data == signed_vote.data {
+            return None;
+        }
+        let report = EquivocationReport::Vote { first: first.clone(), second: signed_vote.clone() };
+        Some(self.record(report))
+    }
+
+    fn record(&mut self, report: EquivocationReport) -> EquivocationReport {
+        self.equivocators.insert(report.validator());
+        self.reports.push(report.clone());
+        report
+    }
+
+    pub fn is_equivocator(&self, validator: &ValidatorIndex) -> bool {
+        self.equivocators.contains(validator)
+    }
+
+    pub fn equivocators(&self) -> &HashSet<ValidatorIndex> {
+        &self.equivocators
+    }
+
+    /// Reports not yet taken by `take_reports`.
+    pub fn reports(&self) -> &[EquivocationReport] {
+        &self.reports
+    }
+
+    pub fn take_reports(&mut self) -> Vec<EquivocationReport> {
+        std::mem::take(&mut self.reports)
+    }
+
+    /// Forgets messages at or below `min_slot`; known equivocators stay marked.
+    pub fn prune_up_to(&mut self, min_slot: Slot) {
+        self.blocks.retain(|(_, slot), _| *slot > min_slot);
+        self.votes.retain(|(_, slot), _| *slot > min_slot);
+    }
+}
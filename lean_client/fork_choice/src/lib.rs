@@ -1,14 +1,13 @@
-pub fn add(left: u64, right: u64) -> u64 {
-    left + right
-}
+//! 3SF-mini fork choice: the block tree, latest votes and LMD-GHOST head.
+pub mod attestation_pool;
+pub mod equivocation;
+pub mod orphans;
+pub mod store;
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn it_works() {
-        let result = add(2, 2);
-        assert_eq!(result, 4);
-    }
-}
+pub use attestation_pool::AttestationPool;
+pub use equivocation::{EquivocationDetector, EquivocationReport};
+pub use orphans::{OrphanPool, MAX_ORPHAN_BLOCKS, ORPHAN_EXPIRY_SLOTS};
+pub use store::{
+    get_fork_choice_head, get_vote_target, supermajority_threshold, BlockImport, ForkChoiceError, ForkChoiceView, Store,
+    MAX_LATE_BLOCKS, MAX_LATE_VOTES,
+};
@@ -0,0 +1,115 @@
+use containers::{block::hash_tree_root, Bytes32, SignedBlock, Slot};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Orphans the store holds at most; once full, the one that arrived first is dropped for a new one.
+pub const MAX_ORPHAN_BLOCKS: usize = 256;
+/// Orphans more than this many slots behind the current slot are dropped, and ones more than
+/// this many slots ahead of it are refused.
+pub const ORPHAN_EXPIRY_SLOTS: u64 = 32;
+
+/// Called with the root of a parent block nobody has sent us yet.
+#[derive(Clone)]
+pub struct MissingParentHandler(pub Arc<dyn Fn(Bytes32) + Send + Sync>);
+
+impl std::fmt::Debug for MissingParentHandler {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("MissingParentHandler")
+    }
+}
+
+/// Blocks whose parent isn't known yet, with their arrival time, indexed by root and by parent root.
+#[derive(Clone, Debug)]
+pub struct OrphanPool {
+    capacity: usize,
+    blocks: HashMap<Bytes32, (SignedBlock, u64)>,
+    by_parent: HashMap<Bytes32, Vec<Bytes32>>,
+}
+
+impl Default for OrphanPool {
+    fn default() -> Self {
+        Self::with_capacity(MAX_ORPHAN_BLOCKS)
+    }
+}
+
+impl OrphanPool {
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self { capacity, blocks: HashMap::new(), by_parent: HashMap::new() }
+    }
+
+    pub fn len(&self) -> usize {
+        self.blocks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.blocks.is_empty()
+    }
+
+    pub fn contains(&self, root: &Bytes32) -> bool {
+        self.blocks.contains_key(root)
+    }
+
+    /// Whether some queued block is waiting for `parent_root`.
+    pub fn has_children(&self, parent_root: &Bytes32) -> bool {
+        self.by_parent.contains_key(parent_root)
+    }
+
+    /// Queues the block, evicting the earliest arrival if the pool is full. Returns `false` if it
+    /// was already queued or its slot is more than `ORPHAN_EXPIRY_SLOTS` after `current_slot`.
+    pub fn insert(&mut self, signed_block: SignedBlock, arrival_ms: u64, current_slot: Slot) -> bool {
+        let root = hash_tree_root(&signed_block.message);
+        if self.blocks.contains_key(&root) || signed_block.message.slot.0 > current_slot.0 + ORPHAN_EXPIRY_SLOTS {
+            return false;
+        }
+        if self.blocks.len() >= self.capacity {
+            let oldest = self
+                .blocks
+                .iter()
+                .min_by_key(|(root, (block, arrival_ms))| (*arrival_ms, block.message.slot, **root))
+                .map(|(root, _)| *root);
+            if let Some(oldest) = oldest {
+                self.remove(&oldest);
+            }
+        }
+        self.by_parent.entry(signed_block.message.parent_root).or_default().push(root);
+        self.blocks.insert(root, (signed_block, arrival_ms));
+        true
+    }
+
+    /// Removes and returns every block waiting for `parent_root`.
+    pub fn take_children(&mut self, parent_root: &Bytes32) -> Vec<(SignedBlock, u64)> {
+        self.by_parent
+            .remove(parent_root)
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|root| self.blocks.remove(root))
+            .collect()
+    }
+
+    /// Drops orphans at or below `min_slot`; returns how many were dropped.
+    pub fn prune_up_to(&mut self, min_slot: Slot) -> usize {
+        let expired: Vec<Bytes32> = self
+            .blocks
+            .iter()
+            .filter(|(_, (block, _))| block.message.slot <= min_slot)
+            .map(|(root, _)| *root)
+            .collect();
+        for root in &expired {
+            self.remove(root);
+        }
+        expired.len()
+    }
+
+    fn remove(&mut self, root: &Bytes32) {
+        let Some((block, _)) = self.blocks.remove(root) else {
+            return;
+        };
+        let parent_root = block.message.parent_root;
+        if let Some(siblings) = self.by_parent.get_mut(&parent_root) {
+            siblings.retain(|sibling| sibling != root);
+            if siblings.is_empty() {
+                self.by_parent.remove(&parent_root);
+            }
+        }
+    }
+}
@@ -0,0 +1,811 @@
+use chain::config::{
+    BasisPoint, FAST_CONFIRM_DUE_BPS, INTERVALS_PER_SLOT, JUSTIFICATION_LOOKBACK_SLOTS, PROPOSER_REORG_CUTOFF_BPS,
+    PROPOSER_SCORE_BOOST_BPS, REORG_HEAD_WEIGHT_THRESHOLD_BPS, REORG_PARENT_WEIGHT_THRESHOLD_BPS, SECONDS_PER_INTERVAL,
+    SLOT_DURATION_MS, VIEW_FREEZE_CUTOFF_BPS,
+};
+use containers::{
+    block::hash_tree_root, Block, Bytes32, Checkpoint, ContainerConfig, SignatureScheme, SignedBlock, SignedVote, Slot,
+    State, StateTransitionError, ValidatorIndex, Vote, Xmss,
+};
+use crate::attestation_pool::AttestationPool;
+use crate::equivocation::EquivocationDetector;
+use crate::orphans::{MissingParentHandler, OrphanPool, ORPHAN_EXPIRY_SLOTS};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+/// Blocks held back after the view froze; further ones are refused until the next slot begins.
+pub const MAX_LATE_BLOCKS: usize = 64;
+/// Validators with a vote held back after the view froze; further ones are refused until the next slot begins.
+pub const MAX_LATE_VOTES: usize = 4096;
+
+/// Reason a block or vote was not added to the fork-choice store.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ForkChoiceError {
+    AnchorStateRootMismatch { expected: Bytes32, actual: Bytes32 },
+    UnknownParent(Bytes32),
+    UnknownBlock(Bytes32),
+    CheckpointSlotMismatch { root: Bytes32, checkpoint_slot: Slot, block_slot: Slot },
+    SourceAfterTarget { source: Slot, target: Slot },
+    FutureVote { vote_slot: Slot, current_slot: Slot },
+    VoteSignature(StateTransitionError),
+    StateTransition(StateTransitionError),
+    LateBlocksFull,
+    LateVotesFull,
+}
+
+impl std::fmt::Display for ForkChoiceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::AnchorStateRootMismatch { expected, actual } =>
+                write!(f, "Anchor block state root mismatch: block claims {:?}, state root is {:?}", expected.0, actual.0),
+            Self::UnknownParent(root) => write!(f, "Unknown parent block {:?}", root.0),
+            Self::UnknownBlock(root) => write!(f, "Vote references unknown block {:?}", root.0),
+            Self::CheckpointSlotMismatch { root, checkpoint_slot, block_slot } =>
+                write!(f, "Checkpoint slot {} does not match block {:?} at slot {}", checkpoint_slot.0, root.0, block_slot.0),
+            Self::SourceAfterTarget { source, target } =>
+                write!(f, "Vote source slot {} is after target slot {}", source.0, target.0),
+            Self::FutureVote { vote_slot, current_slot } =>
+                write!(f, "Vote for slot {} arrived during slot {}", vote_slot.0, current_slot.0),
+            Self::VoteSignature(err) => write!(f, "Vote rejected: {}", err),
+            Self::StateTransition(err) => write!(f, "Block rejected by state transition: {}", err),
+            Self::LateBlocksFull => write!(f, "Already holding {} blocks that arrived after the view froze", MAX_LATE_BLOCKS),
+            Self::LateVotesFull => write!(f, "Already holding {} votes that arrived after the view froze", MAX_LATE_VOTES),
+        }
+    }
+}
+
+impl std::error::Error for ForkChoiceError {}
+
+impl From<StateTransitionError> for ForkChoiceError {
+    fn from(err: StateTransitionError) -> Self {
+        Self::StateTransition(err)
+    }
+}
+
+/// What `Store::on_block` did with a block, by its root.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BlockImport {
+    /// The block is in the block tree, possibly from an earlier call.
+    Imported(Bytes32),
+    /// The view is frozen; the block is imported when the next slot begins.
+    Late(Bytes32),
+    /// The parent is unknown; the block waits in `Store::orphans`.
+    Orphaned(Bytes32),
+}
+
+impl BlockImport {
+    pub fn root(&self) -> Bytes32 {
+        match self {
+            Self::Imported(root) | Self::Late(root) | Self::Orphaned(root) => *root,
+        }
+    }
+}
+
+/// Checkpoints downstream consumers act on, from least to most final.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ForkChoiceView {
+    pub head: Checkpoint,
+    pub safe_target: Checkpoint,
+    pub fast_confirmed: Checkpoint,
+    pub latest_justified: Checkpoint,
+    pub latest_finalized: Checkpoint,
+}
+
+/// Block tree, post-states and latest votes seen by this node.
+#[derive(Clone, Debug)]
+pub struct Store<S: SignatureScheme = Xmss> {
+    /// Intervals elapsed since genesis; see `chain::config::INTERVALS_PER_SLOT`.
+    pub time: u64,
+    pub config: ContainerConfig,
+    /// Checks every block and gossip vote signature, including in the state transition.
+    pub scheme: S,
+    pub head: Bytes32,
+    /// Deepest block backed by a 2/3 supermajority of votes, refreshed at interval 2.
+    /// Validators use it as `Vote::target`.
+    pub safe_target: Checkpoint,
+    /// Deepest block that a supermajority voted for, within a single slot, before the
+    /// `FAST_CONFIRM_DUE_BPS` deadline. Only moves forward along the chain.
+    pub fast_confirmed: Checkpoint,
+    pub latest_justified: Checkpoint,
+    pub latest_finalized: Checkpoint,
+    /// Blocks keyed by `hash_tree_root(Block)`.
+    pub blocks: HashMap<Bytes32, Block>,
+    /// Post-state of every block in `blocks`, under the same key.
+    pub states: HashMap<Bytes32, State>,
+    /// Latest vote per validator, by `Vote::slot`, that counts towards the head.
+    pub latest_known_votes: HashMap<ValidatorIndex, Vote>,
+    /// Gossip votes waiting to be promoted to `latest_known_votes`.
+    pub latest_new_votes: HashMap<ValidatorIndex, Vote>,
+    /// Milliseconds since genesis at which each of `latest_new_votes` was received.
+    pub new_vote_arrivals: HashMap<ValidatorIndex, u64>,
+    /// Signed votes from gossip and blocks, for proposers to pack.
+    pub attestation_pool: AttestationPool,
+    /// Milliseconds since genesis at which each imported block arrived.
+    pub block_arrivals: HashMap<Bytes32, u64>,
+    /// First block of the current slot that arrived before the reorg cutoff.
+    pub proposer_boost_root: Option<Bytes32>,
+    /// Head as of the current slot's view-freeze point.
+    pub frozen_head: Bytes32,
+    /// Blocks received after the view froze, by root, with their arrival time; imported when the
+    /// next slot begins. Holds at most `MAX_LATE_BLOCKS`.
+    pub late_blocks: HashMap<Bytes32, (SignedBlock, u64)>,
+    /// Newest gossip vote per validator received after the view froze; accepted when the next
+    /// slot begins. Holds at most `MAX_LATE_VOTES`.
+    pub late_votes: HashMap<ValidatorIndex, SignedVote>,
+    /// Blocks waiting for their parent to be imported.
+    pub orphans: OrphanPool,
+    pub missing_parent_handler: Option<MissingParentHandler>,
+    /// Conflicting blocks and votes seen so far; equivocators' votes carry no weight.
+    pub equivocations: EquivocationDetector,
+}
+
+impl Store {
+    /// Starts the store from a trusted anchor, e.g. the genesis state and block.
+    pub fn new(anchor_state: State, anchor_block: Block) -> Result<Self, ForkChoiceError> {
+        Self::with_scheme(anchor_state, anchor_block, Xmss)
+    }
+
+    /// Starts the store from a genesis state, anchored at its `genesis_block`.
+    pub fn from_genesis(genesis_state: State) -> Self {
+        let genesis_block = genesis_state.genesis_block();
+        Self::new(genesis_state, genesis_block).expect("genesis block commits to the genesis state")
+    }
+}
+
+impl<S: SignatureScheme> Store<S> {
+    /// Like `Store::new`, verifying signatures with `scheme`.
+    pub fn with_scheme(anchor_state: State, anchor_block: Block, scheme: S) -> Result<Self, ForkChoiceError> {
+        let state_root = hash_tree_root(&anchor_state);
+        if anchor_block.state_root != state_root {
+            return Err(ForkChoiceError::AnchorStateRootMismatch { expected: anchor_block.state_root, actual: state_root });
+        }
+
+        let anchor_root = hash_tree_root(&anchor_block);
+        let anchor = Checkpoint { root: anchor_root, slot: anchor_block.slot };
+        Ok(Self {
+            time: anchor_block.slot.0 * INTERVALS_PER_SLOT,
+            config: anchor_state.config.clone(),
+            scheme,
+            head: anchor_root,
+            safe_target: anchor.clone(),
+            fast_confirmed: anchor.clone(),
+            latest_justified: anchor.clone(),
+            latest_finalized: anchor,
+            blocks: HashMap::from([(anchor_root, anchor_block)]),
+            states: HashMap::from([(anchor_root, anchor_state)]),
+            latest_known_votes: HashMap::new(),
+            latest_new_votes: HashMap::new(),
+            new_vote_arrivals: HashMap::new(),
+            attestation_pool: AttestationPool::default(),
+            block_arrivals: HashMap::new(),
+            proposer_boost_root: None,
+            frozen_head: anchor_root,
+            late_blocks: HashMap::new(),
+            late_votes: HashMap::new(),
+            orphans: OrphanPool::default(),
+            missing_parent_handler: None,
+            equivocations: EquivocationDetector::default(),
+        })
+    }
+
+    /// Asks networking for parents that orphaned blocks are waiting on.
+    pub fn set_missing_parent_handler(&mut self, handler: impl Fn(Bytes32) + Send + Sync + 'static) {
+        self.missing_parent_handler = Some(MissingParentHandler(Arc::new(handler)));
+    }
+
+    /// Imports a block on top of its known parent.
+    /// Once the view is frozen a correctly signed block is only queued, and imported when the
+    /// next slot begins. A block with an unknown parent waits in `orphans` until the parent is imported.
+    pub fn on_block(&mut self, signed_block: SignedBlock) -> Result<BlockImport, ForkChoiceError> {
+        let root = hash_tree_root(&signed_block.message);
+        if self.blocks.contains_key(&root) {
+            return Ok(BlockImport::Imported(root));
+        }
+        if self.is_view_frozen() {
+            return self.queue_late_block(root, signed_block);
+        }
+        self.import_block(signed_block, self.time_ms())
+    }
+
+    fn queue_late_block(&mut self, root: Bytes32, signed_block: SignedBlock) -> Result<BlockImport, ForkChoiceError> {
+        if self.late_blocks.contains_key(&root) {
+            return Ok(BlockImport::Late(root));
+        }
+        let parent_root = signed_block.message.parent_root;
+        if !self.blocks.contains_key(&parent_root) && !self.late_blocks.contains_key(&parent_root) {
+            self.queue_orphan(signed_block, self.time_ms());
+            return Ok(BlockImport::Orphaned(root));
+        }
+        if self.late_blocks.len() >= MAX_LATE_BLOCKS {
+            return Err(ForkChoiceError::LateBlocksFull);
+        }
+        // Every state shares the genesis registry, so the proposer's key is known up front
+        self.states[&self.head].verify_block_signatures(&self.scheme, &signed_block)?;
+        self.late_blocks.insert(root, (signed_block, self.time_ms()));
+        Ok(BlockImport::Late(root))
+    }
+
+    fn import_block(&mut self, signed_block: SignedBlock, arrival_ms: u64) -> Result<BlockImport, ForkChoiceError> {
+        let root = hash_tree_root(&signed_block.message);
+        if self.blocks.contains_key(&root) {
+            return Ok(BlockImport::Imported(root));
+        }
+
+        let parent_root = signed_block.message.parent_root;
+        if !self.blocks.contains_key(&parent_root) {
+            self.queue_orphan(signed_block, arrival_ms);
+            return Ok(BlockImport::Orphaned(root));
+        }
+
+        self.apply_block(signed_block, arrival_ms)?;
+
+        // Descendants that were waiting on this block can follow now; invalid ones are dropped
+        let mut ready = self.orphans.take_children(&root);
+        while let Some((child, child_arrival_ms)) = ready.pop() {
+            if let Ok(child_root) = self.apply_block(child, child_arrival_ms) {
+                ready.extend(self.orphans.take_children(&child_root));
+            }
+        }
+        Ok(BlockImport::Imported(root))
+    }
+
+    fn queue_orphan(&mut self, signed_block: SignedBlock, arrival_ms: u64) {
+        let parent_root = signed_block.message.parent_root;
+        // Only the oldest missing ancestor needs requesting
+        let already_requested = self.orphans.has_children(&parent_root) || self.orphans.contains(&parent_root);
+        if self.orphans.insert(signed_block, arrival_ms, self.current_slot())
+            && !already_requested
+            && let Some(handler) = &self.missing_parent_handler
+        {
+            (handler.0)(parent_root);
+        }
+    }
+
+    fn apply_block(&mut self, signed_block: SignedBlock, arrival_ms: u64) -> Result<Bytes32, ForkChoiceError> {
+        let block = signed_block.message.clone();
+        let root = hash_tree_root(&block);
+
+        let parent_state = self.states.get(&block.parent_root).ok_or(ForkChoiceError::UnknownParent(block.parent_root))?;
+        let state = parent_state.state_transition_with(&self.scheme, signed_block.clone())?;
+
+        self.blocks.insert(root, block.clone());
+        self.states.insert(root, state);
+        self.block_arrivals.insert(root, arrival_ms);
+        if self.proposer_boost_root.is_none() && block.slot == self.current_slot() && self.is_timely(&root) {
+            self.proposer_boost_root = Some(root);
+        }
+
+        if self.equivocations.observe_block(&signed_block).is_some() {
+            self.discount_equivocator(block.proposer_index);
+        }
+
+        // The state transition already filtered these; fork choice only keeps the ones it can place.
+        let mut i: u64 = 0;
+        while let Ok(signed_vote) = block.body.attestations.get(i) {
+            let _ = self.apply_vote(signed_vote, None);
+            i += 1;
+        }
+
+        self.update_head();
+        Ok(root)
+    }
+
+    /// Records a gossip vote received at the store's current time; see `on_attestation_at`.
+    pub fn on_attestation(&mut self, signed_vote: &SignedVote) -> Result<(), ForkChoiceError> {
+        self.on_attestation_at(signed_vote, self.time_ms())
+    }
+
+    /// Records a gossip vote received `received_ms` milliseconds after genesis, which may be
+    /// later than the last tick. It only counts towards the head once promoted by `on_tick`.
+    ///
+    /// A vote received after the current slot's freeze point waits for the next slot, and only
+    /// votes received before `FAST_CONFIRM_DUE_BPS` count towards `fast_confirmed`, even if the
+    /// store is ticked past the deadline after they arrive.
+    pub fn on_attestation_at(&mut self, signed_vote: &SignedVote, received_ms: u64) -> Result<(), ForkChoiceError> {
+        let received_ms = received_ms.max(self.time_ms());
+        let current_slot = self.current_slot();
+        if signed_vote.data.slot > current_slot {
+            return Err(ForkChoiceError::FutureVote { vote_slot: signed_vote.data.slot, current_slot });
+        }
+        let is_late = self.is_past(VIEW_FREEZE_CUTOFF_BPS, received_ms);
+        let validator = ValidatorIndex(signed_vote.data.validator_id.0);
+        if is_late {
+            // Replays and older votes are dropped before paying for their signature
+            if let Some(queued) = self.late_votes.get(&validator) {
+                if queued.data.slot >= signed_vote.data.slot {
+                    return Ok(());
+                }
+            } else if self.late_votes.len() >= MAX_LATE_VOTES {
+                return Err(ForkChoiceError::LateVotesFull);
+            }
+        }
+        // Every state shares the genesis registry; votes packed in blocks are checked by the state transition
+        self.states[&self.head]
+            .verify_vote_signature(&self.scheme, signed_vote)
+            .map_err(ForkChoiceError::VoteSignature)?;
+        if is_late {
+            self.validate_vote(&signed_vote.data)?;
+            self.late_votes.insert(validator, signed_vote.clone());
+            return Ok(());
+        }
+        self.apply_vote(signed_vote, Some(received_ms))
+    }
+
+    /// Advances the store to `time` (seconds since the UNIX epoch) one interval at a time.
+    /// `has_proposal` signals that this node proposes at the interval `time` lands on.
+    /// Tick the store before carrying out the duties due at the same time.
+    pub fn on_tick(&mut self, time: u64, has_proposal: bool) {
+        let target = time.saturating_sub(self.config.genesis_time) / SECONDS_PER_INTERVAL;
+        while self.time < target {
+            let signal_proposal = has_proposal && self.time + 1 == target;
+            self.tick_interval(signal_proposal);
+        }
+    }
+
+    pub fn current_slot(&self) -> Slot {
+        Slot(self.time / INTERVALS_PER_SLOT)
+    }
+
+    pub fn current_interval(&self) -> u64 {
+        self.time % INTERVALS_PER_SLOT
+    }
+
+    pub fn get_head(&self) -> Bytes32 {
+        self.head
+    }
+
+    pub fn get_safe_target(&self) -> Checkpoint {
+        self.safe_target.clone()
+    }
+
+    pub fn view(&self) -> ForkChoiceView {
+        ForkChoiceView {
+            head: Checkpoint { root: self.head, slot: self.blocks[&self.head].slot },
+            safe_target: self.safe_target.clone(),
+            fast_confirmed: self.fast_confirmed.clone(),
+            latest_justified: self.latest_justified.clone(),
+            latest_finalized: self.latest_finalized.clone(),
+        }
+    }
+
+    /// Whether the block arrived within its own slot, before `PROPOSER_REORG_CUTOFF_BPS` of it had passed.
+    pub fn is_timely(&self, root: &Bytes32) -> bool {
+        let (Some(block), Some(&arrival)) = (self.blocks.get(root), self.block_arrivals.get(root)) else {
+            return false;
+        };
+        let slot_start = block.slot.0 * SLOT_DURATION_MS;
+        arrival >= slot_start && arrival - slot_start < PROPOSER_REORG_CUTOFF_BPS.of(SLOT_DURATION_MS)
+    }
+
+    /// Known votes for `root` or any of its descendants.
+    pub fn get_weight(&self, root: &Bytes32) -> usize {
+        self.latest_known_votes.values().filter(|vote| self.is_descendant(vote.head.root, *root)).count()
+    }
+
+    /// Whether `root` is `ancestor` or one of its descendants in the block tree.
+    pub fn is_descendant(&self, root: Bytes32, ancestor: Bytes32) -> bool {
+        let Some(ancestor_slot) = self.blocks.get(&ancestor).map(|block| block.slot) else {
+            return false;
+        };
+        let mut current = root;
+        while let Some(block) = self.blocks.get(&current) {
+            if current == ancestor {
+                return true;
+            }
+            if block.slot <= ancestor_slot {
+                break;
+            }
+            current = block.parent_root;
+        }
+        false
+    }
+
+    /// Block the proposer of `slot` should build on.
+    ///
+    /// Usually the head, but a late head from the previous slot that few validators back is
+    /// skipped in favour of its parent when the parent itself is well supported.
+    pub fn get_proposer_head(&self, slot: Slot) -> Bytes32 {
+        let head = &self.blocks[&self.head];
+        let Some(parent) = self.blocks.get(&head.parent_root) else {
+            return self.head;
+        };
+
+        let num_validators = self.config.num_validators;
+        let is_single_slot_reorg = head.slot.0 + 1 == slot.0 && parent.slot.0 + 1 == head.slot.0;
+        let head_is_weak = (self.get_weight(&self.head) as u64) < REORG_HEAD_WEIGHT_THRESHOLD_BPS.of(num_validators);
+        let parent_is_strong = (self.get_weight(&head.parent_root) as u64) >= REORG_PARENT_WEIGHT_THRESHOLD_BPS.of(num_validators);
+
+        if is_single_slot_reorg && !self.is_timely(&self.head) && head_is_weak && parent_is_strong {
+            head.parent_root
+        } else {
+            self.head
+        }
+    }
+
+    /// Drops every block, state and vote that doesn't descend from `latest_finalized`,
+    /// including the finalized block's own ancestors and any such late messages still held
+    /// back, and returns the removed roots by slot.
+    ///
+    /// Call it whenever `latest_finalized` may have advanced; it is a no-op otherwise.
+    pub fn on_finalized(&mut self) -> Vec<Bytes32> {
+        let finalized_root = self.latest_finalized.root;
+        let mut children: HashMap<Bytes32, Vec<Bytes32>> = HashMap::new();
+        for (root, block) in &self.blocks {
+            children.entry(block.parent_root).or_default().push(*root);
+        }
+
+        let mut canonical = HashSet::from([finalized_root]);
+        let mut stack = vec![finalized_root];
+        while let Some(root) = stack.pop() {
+            for child in children.get(&root).into_iter().flatten() {
+                if canonical.insert(*child) {
+                    stack.push(*child);
+                }
+            }
+        }
+
+        let mut pruned: Vec<(Slot, Bytes32)> = self
+            .blocks
+            .iter()
+            .filter(|(root, _)| !canonical.contains(*root))
+            .map(|(root, block)| (block.slot, *root))
+            .collect();
+        pruned.sort();
+
+        for (_, root) in &pruned {
+            self.blocks.remove(root);
+            self.states.remove(root);
+            self.block_arrivals.remove(root);
+        }
+        self.latest_known_votes.retain(|_, vote| canonical.contains(&vote.head.root));
+        self.latest_new_votes.retain(|_, vote| canonical.contains(&vote.head.root));
+        self.new_vote_arrivals.retain(|validator, _| self.latest_new_votes.contains_key(validator));
+        let finalized_slot = self.latest_finalized.slot;
+        self.late_blocks.retain(|_, (signed_block, _)| {
+            signed_block.message.slot > finalized_slot && canonical.contains(&signed_block.message.parent_root)
+        });
+        self.late_votes.retain(|_, signed_vote| canonical.contains(&signed_vote.data.head.root));
+        self.attestation_pool.retain(|signed_vote| canonical.contains(&signed_vote.data.head.root));
+        if self.proposer_boost_root.is_some_and(|root| !canonical.contains(&root)) {
+            self.proposer_boost_root = None;
+        }
+        for checkpoint in [&mut self.safe_target, &mut self.fast_confirmed, &mut self.latest_justified] {
+            if !canonical.contains(&checkpoint.root) {
+                *checkpoint = self.latest_finalized.clone();
+            }
+        }
+        if !canonical.contains(&self.frozen_head) {
+            self.frozen_head = finalized_root;
+        }
+        self.orphans.prune_up_to(finalized_slot);
+        self.equivocations.prune_up_to(finalized_slot);
+        if !pruned.is_empty() {
+            self.update_head();
+        }
+        self.prune_attestation_pool();
+
+        pruned.into_iter().map(|(_, root)| root).collect()
+    }
+
+    /// Whether `VIEW_FREEZE_CUTOFF_BPS` of the current slot has passed.
+    pub fn is_view_frozen(&self) -> bool {
+        self.is_past(VIEW_FREEZE_CUTOFF_BPS, self.time_ms())
+    }
+
+    /// Whether `time_ms` since genesis is at or after `cutoff` of the current slot, including any
+    /// time in later slots the store hasn't been ticked to yet.
+    fn is_past(&self, cutoff: BasisPoint, time_ms: u64) -> bool {
+        time_ms - self.current_slot().0 * SLOT_DURATION_MS >= cutoff.of(SLOT_DURATION_MS)
+    }
+
+    /// Head validators should vote on.
+    ///
+    /// Votes are due at `VOTE_DUE_BPS`, before the freeze point, so an on-time vote follows the
+    /// live head. A vote cast after the freeze uses the head as of the freeze point, so late
+    /// voters don't act on messages that everyone else will only see in the next slot.
+    pub fn get_frozen_head(&self) -> Bytes32 {
+        if self.is_view_frozen() { self.frozen_head } else { self.head }
+    }
+
+    fn time_ms(&self) -> u64 {
+        self.time * SECONDS_PER_INTERVAL * 1_000
+    }
+
+    fn tick_interval(&mut self, has_proposal: bool) {
+        self.time += 1;
+        if self.current_interval() == 0 {
+            self.proposer_boost_root = None;
+            let expired = self.current_slot().0.saturating_sub(ORPHAN_EXPIRY_SLOTS + 1);
+            self.orphans.prune_up_to(Slot(expired).max(self.latest_finalized.slot));
+            self.apply_late_messages();
+            self.prune_attestation_pool();
+        }
+        if self.just_passed(FAST_CONFIRM_DUE_BPS) {
+            self.update_fast_confirmed();
+        }
+        match self.current_interval() {
+            // The proposer builds on a head that includes everything it has seen
+            0 if has_proposal => self.accept_new_votes(),
+            // Nothing is due; gossip votes are collected as they arrive
+            0 | 1 => {}
+            // Validators vote in this interval, at `VOTE_DUE_BPS`. The store is ticked before their
+            // duties run, so the safe target their votes step back to is updated first, from the
+            // votes received before the interval began
+            2 => self.update_safe_target(),
+            _ => self.accept_new_votes(),
+        }
+        // Nothing moves the head after the freeze point until the next slot
+        if self.is_view_frozen() {
+            self.frozen_head = self.head;
+        }
+    }
+
+    /// Whether the last tick crossed `cutoff` of the current slot.
+    fn just_passed(&self, cutoff: BasisPoint) -> bool {
+        let offset = self.time_ms() - self.current_slot().0 * SLOT_DURATION_MS;
+        let due = cutoff.of(SLOT_DURATION_MS);
+        offset >= due && offset < due + SECONDS_PER_INTERVAL * 1_000
+    }
+
+    /// Only this slot's votes count, whether they came in blocks or over gossip, and gossip
+    /// votes only if they were received before the deadline.
+    fn update_fast_confirmed(&mut self) {
+        let current_slot = self.current_slot();
+        let deadline_ms = current_slot.0 * SLOT_DURATION_MS + FAST_CONFIRM_DUE_BPS.of(SLOT_DURATION_MS);
+        let on_time = |validator: &ValidatorIndex| self.new_vote_arrivals.get(validator).is_some_and(|&ms| ms < deadline_ms);
+        let new_votes = self.latest_new_votes.iter().filter(|(validator, _)| on_time(validator));
+        let mut votes: HashMap<ValidatorIndex, &Vote> = HashMap::new();
+        for (validator, vote) in self.latest_known_votes.iter().chain(new_votes) {
+            if vote.slot == current_slot {
+                votes.insert(*validator, vote);
+            }
+        }
+
+        let min_score = supermajority_threshold(self.config.num_validators);
+        let heads = votes.values().map(|vote| &vote.head);
+        let root = get_fork_choice_head(&self.blocks, self.fast_confirmed.root, heads, min_score);
+        self.fast_confirmed = Checkpoint { root, slot: self.blocks[&root].slot };
+    }
+
+    /// Messages that missed the previous slot's freeze are treated as if they had just arrived.
+    /// Ones that turn out to be invalid are dropped.
+    fn apply_late_messages(&mut self) {
+        // Parents before children, so none of them needs a detour through `orphans`
+        let mut late_blocks: Vec<_> = std::mem::take(&mut self.late_blocks).into_values().collect();
+        late_blocks.sort_by_key(|(signed_block, arrival_ms)| (signed_block.message.slot, *arrival_ms));
+        for (signed_block, arrival_ms) in late_blocks {
+            let _ = self.import_block(signed_block, arrival_ms);
+        }
+        for signed_vote in std::mem::take(&mut self.late_votes).into_values() {
+            let _ = self.apply_vote(&signed_vote, Some(self.time_ms()));
+        }
+        self.update_head();
+    }
+
+    fn accept_new_votes(&mut self) {
+        self.new_vote_arrivals.clear();
+        for (validator, vote) in self.latest_new_votes.drain() {
+            let is_newer = self.latest_known_votes.get(&validator).is_none_or(|known| known.slot < vote.slot);
+            if is_newer {
+                self.latest_known_votes.insert(validator, vote);
+            }
+        }
+        self.update_head();
+    }
+
+    /// Walks down from the justified root, only entering subtrees that a supermajority votes for.
+    fn update_safe_target(&mut self) {
+        // This slot's votes are still pending at interval 2, so they override the known ones
+        let mut votes: HashMap<ValidatorIndex, &Vote> = self.latest_known_votes.iter().map(|(v, vote)| (*v, vote)).collect();
+        for (validator, vote) in &self.latest_new_votes {
+            if votes.get(validator).is_none_or(|known| known.slot < vote.slot) {
+                votes.insert(*validator, vote);
+            }
+        }
+
+        let min_score = supermajority_threshold(self.config.num_validators);
+        let heads = votes.values().map(|vote| &vote.head);
+        let root = get_fork_choice_head(&self.blocks, self.latest_justified.root, heads, min_score);
+        self.safe_target = Checkpoint { root, slot: self.blocks[&root].slot };
+    }
+
+    /// Block votes, with no `received_ms`, count straight away; gossip votes wait in `latest_new_votes`.
+    fn apply_vote(&mut self, signed_vote: &SignedVote, received_ms: Option<u64>) -> Result<(), ForkChoiceError> {
+        let vote = &signed_vote.data;
+        self.validate_vote(vote)?;
+
+        let validator = ValidatorIndex(vote.validator_id.0);
+        if self.equivocations.observe_vote(signed_vote).is_some() {
+            self.discount_equivocator(validator);
+        }
+        if self.equivocations.is_equivocator(&validator) {
+            return Ok(());
+        }
+        let newer_than = |latest: Option<&Vote>| latest.is_none_or(|latest| latest.slot < vote.slot);
+        self.attestation_pool.insert(signed_vote.clone());
+        match received_ms {
+            None => {
+                if newer_than(self.latest_known_votes.get(&validator)) {
+                    self.latest_known_votes.insert(validator, vote.clone());
+                }
+                if self.latest_new_votes.get(&validator).is_some_and(|new| new.slot <= vote.slot) {
+                    self.latest_new_votes.remove(&validator);
+                    self.new_vote_arrivals.remove(&validator);
+                }
+            }
+            Some(received_ms) => {
+                if newer_than(self.latest_known_votes.get(&validator)) && newer_than(self.latest_new_votes.get(&validator)) {
+                    self.latest_new_votes.insert(validator, vote.clone());
+                    self.new_vote_arrivals.insert(validator, received_ms);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Drops the validator's latest votes; `apply_vote` ignores any it sends later.
+    fn discount_equivocator(&mut self, validator: ValidatorIndex) {
+        self.latest_known_votes.remove(&validator);
+        self.latest_new_votes.remove(&validator);
+        self.new_vote_arrivals.remove(&validator);
+        self.attestation_pool.remove_validator(&validator);
+        self.update_head();
+    }
+
+    fn validate_vote(&self, vote: &Vote) -> Result<(), ForkChoiceError> {
+        for checkpoint in [&vote.source, &vote.target, &vote.head] {
+            let block = self.blocks.get(&checkpoint.root).ok_or(ForkChoiceError::UnknownBlock(checkpoint.root))?;
+            if block.slot != checkpoint.slot {
+                return Err(ForkChoiceError::CheckpointSlotMismatch {
+                    root: checkpoint.root,
+                    checkpoint_slot: checkpoint.slot,
+                    block_slot: block.slot,
+                });
+            }
+        }
+        if vote.source.slot > vote.target.slot {
+            return Err(ForkChoiceError::SourceAfterTarget { source: vote.source.slot, target: vote.target.slot });
+        }
+        Ok(())
+    }
+
+    fn update_head(&mut self) {
+        let justified_before = self.latest_justified.clone();
+        // Adopt the highest justified / finalized checkpoint any known state has reached
+        for state in self.states.values() {
+            if state.latest_justified.slot > self.latest_justified.slot && self.blocks.contains_key(&state.latest_justified.root) {
+                self.latest_justified = state.latest_justified.clone();
+            }
+            if state.latest_finalized.slot > self.latest_finalized.slot && self.blocks.contains_key(&state.latest_finalized.root) {
+                self.latest_finalized = state.latest_finalized.clone();
+            }
+        }
+        if self.latest_finalized.slot > self.fast_confirmed.slot {
+            self.fast_confirmed = self.latest_finalized.clone();
+        }
+
+        let heads = self.latest_known_votes.values().map(|vote| &vote.head);
+        let boost = self
+            .proposer_boost_root
+            .map(|root| (root, PROPOSER_SCORE_BOOST_BPS.of(self.config.num_validators) as usize));
+        self.head = lmd_ghost(&self.blocks, self.latest_justified.root, heads, 0, boost);
+        if self.latest_justified != justified_before {
+            self.prune_attestation_pool();
+        }
+    }
+
+    /// Prunes the pool against the head state as a block on top of it would see it, i.e. with
+    /// the head's state root cached so votes targeting the head itself are kept.
+    fn prune_attestation_pool(&mut self) {
+        let state = self.states[&self.head].process_slot();
+        self.attestation_pool.prune(&state);
+    }
+}
+
+/// Checkpoint an honest validator should vote for as `Vote::target`.
+///
+/// Starts at the head validators vote on, `Store::get_frozen_head`, so a vote's target is
+/// always an ancestor of its head. From there it steps back towards the safe target by at most
+/// `JUSTIFICATION_LOOKBACK_SLOTS` blocks, then keeps stepping back until the slot is
+/// justifiable after the latest finalized slot, so `process_attestations` won't ignore it.
+pub fn get_vote_target<S: SignatureScheme>(store: &Store<S>) -> Checkpoint {
+    let safe_slot = store.blocks[&store.safe_target.root].slot;
+    let mut target = store.get_frozen_head();
+    for _ in 0..JUSTIFICATION_LOOKBACK_SLOTS {
+        let block = &store.blocks[&target];
+        if block.slot <= safe_slot || !store.blocks.contains_key(&block.parent_root) {
+            break;
+        }
+        target = block.parent_root;
+    }
+
+    let finalized_slot = store.latest_finalized.slot;
+    loop {
+        let block = &store.blocks[&target];
+        if block.slot <= finalized_slot
+            || block.slot.is_justifiable_after(finalized_slot)
+            || !store.blocks.contains_key(&block.parent_root)
+        {
+            break;
+        }
+        target = block.parent_root;
+    }
+
+    Checkpoint { root: target, slot: store.blocks[&target].slot }
+}
+
+/// Smallest number of votes out of `num_validators` that makes a 2/3 supermajority,
+/// matching the `3 * count >= 2 * num_validators` rule in `State::process_attestations`.
+pub fn supermajority_threshold(num_validators: u64) -> usize {
+    (2 * num_validators).div_ceil(3) as usize
+}
+
+/// LMD-GHOST from `root`: repeatedly descend into the child with the most vote weight,
+/// breaking ties by slot and then by root. Children weighing less than `min_score` are ignored.
+pub fn get_fork_choice_head<'a>(
+    blocks: &HashMap<Bytes32, Block>,
+    root: Bytes32,
+    votes: impl IntoIterator<Item = &'a Checkpoint>,
+    min_score: usize,
+) -> Bytes32 {
+    lmd_ghost(blocks, root, votes, min_score, None)
+}
+
+/// `get_fork_choice_head` with an optional `(block, weight)` proposer boost.
+fn lmd_ghost<'a>(
+    blocks: &HashMap<Bytes32, Block>,
+    root: Bytes32,
+    votes: impl IntoIterator<Item = &'a Checkpoint>,
+    min_score: usize,
+    boost: Option<(Bytes32, usize)>,
+) -> Bytes32 {
+    let root_slot = match blocks.get(&root) {
+        Some(block) => block.slot,
+        None => return root,
+    };
+
+    let mut weights = vote_weights(blocks, root_slot, votes);
+    if let Some((boost_root, boost)) = boost {
+        add_weight(blocks, root_slot, boost_root, boost, &mut weights);
+    }
+
+    let mut children: HashMap<Bytes32, Vec<Bytes32>> = HashMap::new();
+    for (block_root, block) in blocks {
+        if blocks.contains_key(&block.parent_root) && weights.get(block_root).copied().unwrap_or(0) >= min_score {
+            children.entry(block.parent_root).or_default().push(*block_root);
+        }
+    }
+
+    let mut head = root;
+    while let Some(candidates) = children.get(&head) {
+        head = *candidates
+            .iter()
+            .max_by_key(|&&child| (weights.get(&child).copied().unwrap_or(0), blocks[&child].slot, child))
+            .expect("children lists are never empty");
+    }
+    head
+}
+
+/// Every vote adds weight to its head and each ancestor after `root_slot`.
+fn vote_weights<'a>(
+    blocks: &HashMap<Bytes32, Block>,
+    root_slot: Slot,
+    votes: impl IntoIterator<Item = &'a Checkpoint>,
+) -> HashMap<Bytes32, usize> {
+    let mut weights = HashMap::new();
+    for vote in votes {
+        add_weight(blocks, root_slot, vote.root, 1, &mut weights);
+    }
+    weights
+}
+
+fn add_weight(blocks: &HashMap<Bytes32, Block>, root_slot: Slot, from: Bytes32, weight: usize, weights: &mut HashMap<Bytes32, usize>) {
+    let mut current = from;
+    while let Some(block) = blocks.get(&current) {
+        if block.slot <= root_slot {
+            break;
+        }
+        *weights.entry(current).or_default() += weight;
+        current = block.parent_root;
+    }
+}
@@ -0,0 +1,131 @@
+// tests/attestation_pool.rs
+use containers::{Block, BlockBody, Bytes32, SignedVote, Slot, State, ValidatorIndex};
+use fork_choice::{AttestationPool, Store};
+
+mod common;
+use common::*;
+
+/// State a block at `slot` on top of `parent` packs its votes against.
+fn block_state(store: &Store, parent: Bytes32, slot: u64) -> State {
+    let state = store.states[&parent].process_slots(Slot(slot)).unwrap();
+    let block = Block {
+        slot: Slot(slot),
+        proposer_index: ValidatorIndex(slot % NUM_VALIDATORS),
+        parent_root: parent,
+        state_root: Bytes32::default(),
+        body: BlockBody { attestations: Default::default() },
+    };
+    state.process_block_header(&block).unwrap()
+}
+
+fn validators(votes: &[SignedVote]) -> Vec<u64> {
+    votes.iter().map(|signed_vote| signed_vote.data.validator_id.0).collect()
+}
+
+/// Genesis, `a` at slot 1 and `b` at slot 2.
+fn chain() -> (Store, Bytes32, Bytes32) {
+    let mut store = genesis_store();
+    let genesis_root = store.head;
+    let a = add_block(&mut store, genesis_root, 1, vec![]);
+    let b = add_block(&mut store, a, 2, vec![]);
+    (store, a, b)
+}
+
+#[test]
+fn test_insert_keeps_latest_vote_per_validator_and_target() {
+    let (store, a, b) = chain();
+    let source = store.latest_justified.clone();
+    let mut pool = AttestationPool::default();
+
+    assert!(pool.insert(head_vote(&store, 0, 1, a)));
+    assert!(!pool.insert(head_vote(&store, 0, 1, a)));
+    let later = vote(0, 2, checkpoint(&store, b), checkpoint(&store, a), source);
+    assert!(pool.insert(later.clone()));
+    assert!(!pool.insert(head_vote(&store, 0, 1, a)));
+    assert!(pool.insert(head_vote(&store, 0, 2, b)));
+
+    assert_eq!(pool.len(), 2);
+    assert_eq!(pool.votes_for(&checkpoint(&store, a)).cloned().collect::<Vec<_>>(), vec![later]);
+
+    pool.remove_validator(&ValidatorIndex(0));
+    assert!(pool.is_empty());
+}
+
+#[test]
+fn test_select_puts_targets_closest_to_supermajority_first() {
+    let (store, a, b) = chain();
+    let mut pool = AttestationPool::default();
+    pool.insert(head_vote(&store, 0, 2, a));
+    pool.insert(head_vote(&store, 2, 2, b));
+    pool.insert(head_vote(&store, 1, 2, b));
+
+    let state = block_state(&store, b, 3);
+    // Two of three validators justify `b`; `a` would still be a vote short
+    assert_eq!(validators(&pool.select(&state, 4096)), vec![1, 2, 0]);
+    assert_eq!(validators(&pool.select(&state, 2)), vec![1, 2]);
+}
+
+#[test]
+fn test_select_skips_votes_the_state_would_reject_or_has_counted() {
+    let (mut store, a, b) = chain();
+    let mut pool = AttestationPool::default();
+    let unjustified_source = vote(1, 2, checkpoint(&store, b), checkpoint(&store, b), checkpoint(&store, a));
+    pool.insert(unjustified_source);
+    pool.insert(head_vote(&store, 0, 2, a));
+
+    assert_eq!(validators(&pool.select(&block_state(&store, b, 3), 4096)), vec![0]);
+
+    // Once a block counted validator 0 for `a`, packing its vote again gains nothing
+    let counted = head_vote(&store, 0, 2, a);
+    let c = add_block(&mut store, b, 3, vec![counted]);
+    assert!(pool.select(&block_state(&store, c, 4), 4096).is_empty());
+}
+
+#[test]
+fn test_prune_drops_votes_for_justified_targets() {
+    let (mut store, a, b) = chain();
+    let votes: Vec<_> = (0..NUM_VALIDATORS).map(|validator| head_vote(&store, validator, 2, a)).collect();
+    let mut pool = AttestationPool::default();
+    for signed_vote in &votes {
+        pool.insert(signed_vote.clone());
+    }
+    pool.insert(head_vote(&store, 0, 2, b));
+
+    let c = add_block(&mut store, b, 3, votes);
+    assert_eq!(store.states[&c].latest_justified, checkpoint(&store, a));
+
+    pool.prune(&store.states[&c]);
+    // The vote for `b` still counts in `c`, but its genesis source is older than `a` now
+    assert!(pool.is_empty());
+
+    let genesis_source = store.states[&b].latest_justified.clone();
+    assert!(!pool.insert(vote(2, 3, checkpoint(&store, b), checkpoint(&store, b), genesis_source)));
+    assert!(pool.insert(head_vote(&store, 1, 3, b)));
+    assert_eq!(pool.targets().cloned().collect::<Vec<_>>(), vec![checkpoint(&store, b)]);
+}
+
+#[test]
+fn test_store_prunes_pool_when_justification_advances() {
+    let (mut store, a, b) = chain();
+    let votes: Vec<_> = (0..NUM_VALIDATORS).map(|validator| head_vote(&store, validator, 2, a)).collect();
+    tick_to(&mut store, 2, 1);
+    store.on_attestation(&head_vote(&store, 0, 2, b)).unwrap();
+    assert_eq!(store.attestation_pool.len(), 1);
+
+    add_block(&mut store, b, 3, votes);
+
+    assert_eq!(store.latest_justified, checkpoint(&store, a));
+    assert!(store.attestation_pool.is_empty());
+}
+
+#[test]
+fn test_store_pools_gossip_and_block_votes() {
+    let (mut store, a, b) = chain();
+    tick_to(&mut store, 2, 1);
+    store.on_attestation(&head_vote(&store, 1, 2, b)).unwrap();
+    let packed = head_vote(&store, 0, 2, a);
+    add_block(&mut store, b, 3, vec![packed]);
+
+    assert_eq!(store.attestation_pool.len(), 2);
+    assert_eq!(validators(&store.attestation_pool.votes_for(&checkpoint(&store, b)).cloned().collect::<Vec<_>>()), vec![1]);
+}
@@ -0,0 +1,77 @@
+// tests/common.rs
+// Shared by every test binary; not every binary uses every helper.
+#![allow(dead_code)]
+
+use containers::{
+    block::{hash_tree_root, Block, BlockBody, SignedBlock},
+    ssz::PersistentList as List,
+    Bytes32, SignedVote, Slot, State, Uint64, ValidatorIndex,
+};
+use fork_choice::Store;
+
+#[allow(unused_imports)]
+pub use test_support::{checkpoint, sign_block, test_key, test_pubkeys, tick_to, vote, MAX_TEST_VALIDATORS};
+
+pub const NUM_VALIDATORS: u64 = 3;
+
+/// Genesis state and the anchor block committing to it.
+pub fn genesis() -> (State, Block) {
+    genesis_with(NUM_VALIDATORS)
+}
+
+pub fn genesis_with(num_validators: u64) -> (State, Block) {
+    let state = State::generate_genesis(Uint64(0), &test_pubkeys(num_validators));
+    let block = state.genesis_block();
+    (state, block)
+}
+
+pub fn genesis_store() -> Store {
+    genesis_store_with(NUM_VALIDATORS)
+}
+
+pub fn genesis_store_with(num_validators: u64) -> Store {
+    Store::from_genesis(genesis_with(num_validators).0)
+}
+
+/// A valid block at `slot` on top of `parent`, with the round-robin proposer, a correct state root and its signature.
+pub fn build_block(store: &Store, parent: Bytes32, slot: u64, votes: Vec<SignedVote>) -> SignedBlock {
+    let parent_state = &store.states[&parent];
+    let state = parent_state.process_slots(Slot(slot)).expect("slot is after parent");
+
+    let mut attestations = List::default();
+    for vote in votes {
+        attestations.push(vote).expect("within attestation limit");
+    }
+    let mut block = Block {
+        slot: Slot(slot),
+        proposer_index: ValidatorIndex(slot % parent_state.config.num_validators),
+        parent_root: parent,
+        state_root: Bytes32::default(),
+        body: BlockBody { attestations },
+    };
+    let post = state.process_block(&block).expect("block is valid");
+    block.state_root = hash_tree_root(&post);
+    sign_block(block)
+}
+
+/// Builds and imports a block, returning its root.
+pub fn add_block(store: &mut Store, parent: Bytes32, slot: u64, votes: Vec<SignedVote>) -> Bytes32 {
+    let block = build_block(store, parent, slot, votes);
+    store.on_block(block).expect("block imports").root()
+}
+
+/// A vote whose head and target are both `root`, sourced from the store's justified checkpoint.
+pub fn head_vote(store: &Store, validator: u64, slot: u64, root: Bytes32) -> SignedVote {
+    let target = checkpoint(store, root);
+    vote(validator, slot, target.clone(), target, store.latest_justified.clone())
+}
+
+/// Delivers gossip votes and ticks to the end of `slot`, where they become known.
+pub fn deliver_votes(store: &mut Store, slot: u64, votes: &[SignedVote]) {
+    tick_to(store, slot, 1);
+    for vote in votes {
+        store.on_attestation(vote).expect("vote is valid");
+    }
+    tick_to(store, slot, 3);
+}
+
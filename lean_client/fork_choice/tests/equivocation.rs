@@ -0,0 +1,84 @@
+// tests/equivocation.rs
+use containers::{Slot, ValidatorIndex};
+use fork_choice::EquivocationReport;
+
+mod common;
+use common::*;
+
+#[test]
+fn test_two_blocks_for_one_slot_are_reported() {
+    let mut store = genesis_store();
+    let genesis_root = store.head;
+    let a = add_block(&mut store, genesis_root, 1, vec![]);
+
+    // Validator 2 proposes slot 2 on both `a` and genesis
+    let first = build_block(&store, a, 2, vec![]);
+    let second = build_block(&store, genesis_root, 2, vec![]);
+    store.on_block(first.clone()).unwrap();
+    assert!(store.equivocations.reports().is_empty());
+    store.on_block(second.clone()).unwrap();
+
+    // Both blocks stay in the tree; only the proposer is flagged
+    assert_eq!(store.blocks.len(), 4);
+    let reports = store.equivocations.take_reports();
+    assert_eq!(reports, vec![EquivocationReport::Block { first, second }]);
+    assert_eq!(reports[0].validator(), ValidatorIndex(2));
+    assert_eq!(reports[0].slot(), Slot(2));
+    assert!(store.equivocations.is_equivocator(&ValidatorIndex(2)));
+    assert!(store.equivocations.reports().is_empty());
+}
+
+#[test]
+fn test_conflicting_votes_are_reported() {
+    let mut store = genesis_store();
+    let genesis_root = store.head;
+    let a = add_block(&mut store, genesis_root, 1, vec![]);
+    let b = add_block(&mut store, genesis_root, 2, vec![]);
+
+    let first = head_vote(&store, 0, 2, a);
+    let second = head_vote(&store, 0, 2, b);
+    deliver_votes(&mut store, 2, &[first.clone(), second.clone()]);
+
+    assert_eq!(store.equivocations.reports(), [EquivocationReport::Vote { first, second }]);
+    assert!(!store.latest_known_votes.contains_key(&ValidatorIndex(0)));
+}
+
+#[test]
+fn test_repeated_vote_is_not_an_equivocation() {
+    let mut store = genesis_store();
+    let genesis_root = store.head;
+    let a = add_block(&mut store, genesis_root, 1, vec![]);
+
+    // Seen over gossip first, then packed into a block
+    let vote = head_vote(&store, 0, 1, a);
+    deliver_votes(&mut store, 1, std::slice::from_ref(&vote));
+    add_block(&mut store, a, 2, vec![vote]);
+
+    assert!(store.equivocations.reports().is_empty());
+    assert!(store.latest_known_votes.contains_key(&ValidatorIndex(0)));
+}
+
+#[test]
+fn test_equivocator_loses_its_weight() {
+    let mut store = genesis_store();
+    let genesis_root = store.head;
+    let a = add_block(&mut store, genesis_root, 1, vec![]);
+    let b = add_block(&mut store, genesis_root, 2, vec![]);
+
+    let votes = [head_vote(&store, 0, 2, a), head_vote(&store, 1, 2, a), head_vote(&store, 2, 2, b)];
+    deliver_votes(&mut store, 2, &votes);
+    assert_eq!(store.get_head(), a);
+
+    // Validator 0 signs a second slot-2 vote; both of its votes stop counting and `b` wins the tie
+    tick_to(&mut store, 3, 0);
+    let conflicting = head_vote(&store, 0, 2, b);
+    store.on_attestation(&conflicting).unwrap();
+    assert_eq!(store.get_weight(&a), 1);
+    assert_eq!(store.get_head(), b);
+
+    // Later votes from the equivocator are ignored as well
+    let later = head_vote(&store, 0, 3, a);
+    deliver_votes(&mut store, 3, &[later]);
+    assert!(!store.latest_known_votes.contains_key(&ValidatorIndex(0)));
+    assert_eq!(store.get_head(), b);
+}
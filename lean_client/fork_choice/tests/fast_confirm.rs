@@ -0,0 +1,127 @@
+// tests/fast_confirm.rs
+use chain::config::{FAST_CONFIRM_DUE_BPS, SLOT_DURATION_MS};
+use containers::{Checkpoint, Slot};
+use fork_choice::ForkChoiceView;
+
+mod common;
+use common::*;
+
+#[test]
+fn test_fast_confirmed_starts_at_anchor() {
+    let store = genesis_store();
+    assert_eq!(store.fast_confirmed, Checkpoint { root: store.head, slot: Slot(0) });
+}
+
+#[test]
+fn test_supermajority_before_deadline_fast_confirms() {
+    let mut store = genesis_store();
+    let genesis_root = store.head;
+    let a = add_block(&mut store, genesis_root, 1, vec![]);
+    let a2 = add_block(&mut store, a, 2, vec![]);
+
+    tick_to(&mut store, 2, 1);
+    store.on_attestation(&head_vote(&store, 0, 2, a2)).unwrap();
+    store.on_attestation(&head_vote(&store, 1, 2, a)).unwrap();
+    tick_to(&mut store, 2, 2);
+    assert_eq!(store.fast_confirmed.root, genesis_root);
+
+    // 75% into the slot: both votes back `a`, only one backs `a2`
+    tick_to(&mut store, 2, 3);
+    assert_eq!(store.fast_confirmed, Checkpoint { root: a, slot: Slot(1) });
+}
+
+#[test]
+fn test_minority_does_not_fast_confirm() {
+    let mut store = genesis_store();
+    let genesis_root = store.head;
+    let a = add_block(&mut store, genesis_root, 1, vec![]);
+    let vote = head_vote(&store, 0, 1, a);
+    deliver_votes(&mut store, 1, &[vote]);
+    assert_eq!(store.fast_confirmed.root, genesis_root);
+}
+
+#[test]
+fn test_votes_from_earlier_slots_do_not_count() {
+    let mut store = genesis_store();
+    let genesis_root = store.head;
+    let a = add_block(&mut store, genesis_root, 1, vec![]);
+    let vote = head_vote(&store, 0, 1, a);
+    deliver_votes(&mut store, 1, &[vote]);
+    let vote = head_vote(&store, 1, 2, a);
+    deliver_votes(&mut store, 2, &[vote]);
+
+    // Two validators back `a`, but never within the same slot
+    assert_eq!(store.latest_known_votes.len(), 2);
+    assert_eq!(store.fast_confirmed.root, genesis_root);
+}
+
+#[test]
+fn test_votes_after_deadline_wait_for_next_slot() {
+    let mut store = genesis_store();
+    let genesis_root = store.head;
+    let a = add_block(&mut store, genesis_root, 1, vec![]);
+    tick_to(&mut store, 1, 3);
+    for v in 0..3 {
+        store.on_attestation(&head_vote(&store, v, 1, a)).unwrap();
+    }
+    tick_to(&mut store, 2, 3);
+    assert_eq!(store.fast_confirmed.root, genesis_root);
+}
+
+#[test]
+fn test_votes_received_after_deadline_do_not_count_before_the_tick() {
+    let deadline_ms = SLOT_DURATION_MS + FAST_CONFIRM_DUE_BPS.of(SLOT_DURATION_MS);
+    let fast_confirmed = |received_ms: u64| {
+        let mut store = genesis_store();
+        let genesis_root = store.head;
+        let a = add_block(&mut store, genesis_root, 1, vec![]);
+        tick_to(&mut store, 1, 2);
+        store.on_attestation_at(&head_vote(&store, 0, 1, a), deadline_ms - 1).unwrap();
+        store.on_attestation_at(&head_vote(&store, 1, 1, a), received_ms).unwrap();
+        // The store only learns that the deadline passed after both votes arrived
+        tick_to(&mut store, 1, 3);
+        (store.fast_confirmed.root == a, store.late_votes.len())
+    };
+
+    assert_eq!(fast_confirmed(deadline_ms - 1), (true, 0));
+    assert_eq!(fast_confirmed(deadline_ms), (false, 1));
+}
+
+#[test]
+fn test_fast_confirmed_does_not_move_backwards() {
+    let mut store = genesis_store();
+    let genesis_root = store.head;
+    let a = add_block(&mut store, genesis_root, 1, vec![]);
+    let b = add_block(&mut store, genesis_root, 2, vec![]);
+    let votes: Vec<_> = (0..3).map(|v| head_vote(&store, v, 1, a)).collect();
+    deliver_votes(&mut store, 1, &votes);
+    assert_eq!(store.fast_confirmed.root, a);
+
+    // A supermajority for a conflicting branch doesn't revert it
+    let votes: Vec<_> = (0..3).map(|v| head_vote(&store, v, 2, b)).collect();
+    deliver_votes(&mut store, 2, &votes);
+    assert_eq!(store.get_head(), b);
+    assert_eq!(store.fast_confirmed.root, a);
+}
+
+#[test]
+fn test_view_reports_every_checkpoint() {
+    let mut store = genesis_store();
+    let genesis_root = store.head;
+    let a = add_block(&mut store, genesis_root, 1, vec![]);
+    let votes: Vec<_> = (0..2).map(|v| head_vote(&store, v, 1, a)).collect();
+    deliver_votes(&mut store, 1, &votes);
+
+    let anchor = Checkpoint { root: genesis_root, slot: Slot(0) };
+    let a_checkpoint = Checkpoint { root: a, slot: Slot(1) };
+    assert_eq!(
+        store.view(),
+        ForkChoiceView {
+            head: a_checkpoint.clone(),
+            safe_target: a_checkpoint.clone(),
+            fast_confirmed: a_checkpoint,
+            latest_justified: anchor.clone(),
+            latest_finalized: anchor,
+        }
+    );
+}
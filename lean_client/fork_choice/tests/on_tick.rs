@@ -0,0 +1,156 @@
+// tests/on_tick.rs
+use chain::config::{INTERVALS_PER_SLOT, SECONDS_PER_INTERVAL};
+use containers::Slot;
+use fork_choice::{get_vote_target, ForkChoiceError, Store};
+
+mod common;
+use common::*;
+
+#[test]
+fn test_on_tick_advances_by_interval() {
+    let mut store = genesis_store();
+    tick_to(&mut store, 2, 3);
+    assert_eq!(store.time, 2 * INTERVALS_PER_SLOT + 3);
+    assert_eq!(store.current_slot(), Slot(2));
+    assert_eq!(store.current_interval(), 3);
+
+    // Time never goes backwards
+    store.on_tick(0, false);
+    assert_eq!(store.time, 2 * INTERVALS_PER_SLOT + 3);
+}
+
+#[test]
+fn test_on_tick_counts_from_genesis_time() {
+    let (mut state, _) = genesis();
+    state.config.genesis_time = 1_000;
+    let block = containers::Block { state_root: containers::block::hash_tree_root(&state), ..Default::default() };
+    let mut store = Store::new(state, block).unwrap();
+
+    store.on_tick(999, false);
+    assert_eq!(store.time, 0);
+    store.on_tick(1_000 + 5 * SECONDS_PER_INTERVAL, false);
+    assert_eq!(store.time, 5);
+}
+
+#[test]
+fn test_gossip_votes_count_only_after_promotion() {
+    let mut store = genesis_store();
+    let genesis_root = store.head;
+    let a = add_block(&mut store, genesis_root, 1, vec![]);
+    let b = add_block(&mut store, genesis_root, 2, vec![]);
+    assert_eq!(store.get_head(), b);
+
+    tick_to(&mut store, 2, 1);
+    store.on_attestation(&head_vote(&store, 0, 2, a)).unwrap();
+    store.on_attestation(&head_vote(&store, 1, 2, a)).unwrap();
+    assert_eq!(store.latest_new_votes.len(), 2);
+    assert_eq!(store.get_head(), b);
+
+    tick_to(&mut store, 2, 2);
+    assert_eq!(store.get_head(), b);
+
+    tick_to(&mut store, 2, 3);
+    assert!(store.latest_new_votes.is_empty());
+    assert_eq!(store.latest_known_votes.len(), 2);
+    assert_eq!(store.get_head(), a);
+}
+
+#[test]
+fn test_interval_zero_promotes_only_for_proposer() {
+    let head_at_next_slot = |has_proposal: bool| {
+        let mut store = genesis_store();
+        let genesis_root = store.head;
+        let a = add_block(&mut store, genesis_root, 1, vec![]);
+        add_block(&mut store, genesis_root, 2, vec![]);
+
+        // Arrives after the interval 3 promotion of slot 2
+        tick_to(&mut store, 2, 3);
+        store.on_attestation(&head_vote(&store, 0, 2, a)).unwrap();
+        store.on_tick(3 * INTERVALS_PER_SLOT * SECONDS_PER_INTERVAL, has_proposal);
+        (store.get_head() == a, store.latest_new_votes.len())
+    };
+
+    assert_eq!(head_at_next_slot(false), (false, 1));
+    assert_eq!(head_at_next_slot(true), (true, 0));
+}
+
+#[test]
+fn test_future_gossip_vote_is_rejected() {
+    let mut store = genesis_store();
+    let genesis_root = store.head;
+    let a = add_block(&mut store, genesis_root, 1, vec![]);
+
+    let result = store.on_attestation(&head_vote(&store, 0, 2, a));
+    assert_eq!(result, Err(ForkChoiceError::FutureVote { vote_slot: Slot(2), current_slot: Slot(0) }));
+}
+
+#[test]
+fn test_block_votes_are_known_immediately() {
+    let mut store = genesis_store();
+    let genesis_root = store.head;
+    let a = add_block(&mut store, genesis_root, 1, vec![]);
+    let b = add_block(&mut store, genesis_root, 2, vec![]);
+
+    // Pending gossip vote for `a` is superseded by the same validator's newer vote packed in a block
+    tick_to(&mut store, 2, 1);
+    store.on_attestation(&head_vote(&store, 0, 1, a)).unwrap();
+    let packed = head_vote(&store, 0, 2, b);
+    add_block(&mut store, b, 3, vec![packed]);
+
+    assert!(store.latest_new_votes.is_empty());
+    assert_eq!(store.latest_known_votes[&containers::ValidatorIndex(0)].head.root, b);
+}
+
+#[test]
+fn test_safe_target_updates_at_interval_two() {
+    let mut store = genesis_store();
+    let genesis_root = store.head;
+    let a = add_block(&mut store, genesis_root, 1, vec![]);
+
+    tick_to(&mut store, 1, 1);
+    store.on_attestation(&head_vote(&store, 0, 1, a)).unwrap();
+    tick_to(&mut store, 1, 2);
+    // One vote out of three is short of the supermajority
+    assert_eq!(store.safe_target.root, genesis_root);
+
+    tick_to(&mut store, 2, 1);
+    store.on_attestation(&head_vote(&store, 1, 2, a)).unwrap();
+    store.on_attestation(&head_vote(&store, 2, 2, a)).unwrap();
+    tick_to(&mut store, 2, 2);
+    assert_eq!(store.safe_target.root, a);
+}
+
+#[test]
+fn test_safe_target_is_updated_before_interval_two_votes() {
+    let mut store = genesis_store();
+    let genesis_root = store.head;
+    let a = add_block(&mut store, genesis_root, 1, vec![]);
+    let b = add_block(&mut store, a, 2, vec![]);
+
+    tick_to(&mut store, 2, 1);
+    store.on_attestation(&head_vote(&store, 0, 2, b)).unwrap();
+    store.on_attestation(&head_vote(&store, 1, 2, b)).unwrap();
+    assert_eq!(get_vote_target(&store).root, genesis_root);
+
+    // Votes due in interval 2 already step back only as far as the updated safe target
+    tick_to(&mut store, 2, 2);
+    assert_eq!(store.safe_target.root, b);
+    assert_eq!(get_vote_target(&store), checkpoint(&store, b));
+}
+
+#[test]
+fn test_same_ticks_and_messages_give_same_view() {
+    let run = || {
+        let mut store = genesis_store();
+        let genesis_root = store.head;
+        let a = add_block(&mut store, genesis_root, 1, vec![]);
+        let b = add_block(&mut store, genesis_root, 2, vec![]);
+        tick_to(&mut store, 2, 1);
+        for (v, root) in [(0, a), (1, b), (2, a)] {
+            store.on_attestation(&head_vote(&store, v, 2, root)).unwrap();
+        }
+        tick_to(&mut store, 3, 0);
+        (store.head, store.safe_target.clone(), store.latest_justified.clone(), store.time)
+    };
+    assert_eq!(run(), run());
+}
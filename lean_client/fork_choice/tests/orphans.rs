@@ -0,0 +1,130 @@
+// tests/orphans.rs
+use containers::{Bytes32, SignedBlock, Slot};
+use fork_choice::{OrphanPool, Store, ORPHAN_EXPIRY_SLOTS};
+use std::sync::{Arc, Mutex};
+
+mod common;
+use common::*;
+
+/// Records every root the store asks networking for.
+fn record_requests(store: &mut Store) -> Arc<Mutex<Vec<Bytes32>>> {
+    let requests = Arc::new(Mutex::new(Vec::new()));
+    let sink = requests.clone();
+    store.set_missing_parent_handler(move |root| sink.lock().unwrap().push(root));
+    requests
+}
+
+/// Builds `a(1) <- b(2) <- c(3)` on genesis in a scratch store, so the blocks can be delivered in any order.
+fn chain_of_three(store: &Store) -> [SignedBlock; 3] {
+    let mut scratch = store.clone();
+    let a = build_block(&scratch, scratch.head, 1, vec![]);
+    let a_root = scratch.on_block(a.clone()).unwrap().root();
+    let b = build_block(&scratch, a_root, 2, vec![]);
+    let b_root = scratch.on_block(b.clone()).unwrap().root();
+    let c = build_block(&scratch, b_root, 3, vec![]);
+    [a, b, c]
+}
+
+fn root_of(block: &SignedBlock) -> Bytes32 {
+    containers::block::hash_tree_root(&block.message)
+}
+
+#[test]
+fn test_orphans_are_imported_once_parent_arrives() {
+    let mut store = genesis_store();
+    let requests = record_requests(&mut store);
+    let [a, b, c] = chain_of_three(&store);
+
+    store.on_block(c.clone()).unwrap();
+    store.on_block(b.clone()).unwrap();
+    // A duplicate of a queued orphan asks for nothing new
+    store.on_block(c.clone()).unwrap();
+    assert_eq!(store.orphans.len(), 2);
+    assert_eq!(store.blocks.len(), 1);
+    assert_eq!(*requests.lock().unwrap(), vec![root_of(&b), root_of(&a)]);
+
+    store.on_block(a).unwrap();
+    assert!(store.orphans.is_empty());
+    assert_eq!(store.blocks.len(), 4);
+    assert_eq!(store.get_head(), root_of(&c));
+}
+
+#[test]
+fn test_missing_parent_is_requested_once() {
+    let mut store = genesis_store();
+    let requests = record_requests(&mut store);
+    let [a, b, _] = chain_of_three(&store);
+
+    // A sibling of `b` with the same missing parent
+    let mut sibling = b.clone();
+    sibling.message.state_root = Bytes32::default();
+    store.on_block(b).unwrap();
+    store.on_block(sibling).unwrap();
+
+    assert_eq!(store.orphans.len(), 2);
+    assert_eq!(*requests.lock().unwrap(), vec![root_of(&a)]);
+}
+
+#[test]
+fn test_invalid_orphan_is_dropped_on_import() {
+    let mut store = genesis_store();
+    let [a, mut b, _] = chain_of_three(&store);
+    b.message.state_root = Bytes32::default();
+
+    store.on_block(b).unwrap();
+    store.on_block(a.clone()).unwrap();
+    assert!(store.orphans.is_empty());
+    assert_eq!(store.blocks.len(), 2);
+    assert_eq!(store.get_head(), root_of(&a));
+}
+
+#[test]
+fn test_orphans_expire_by_slot() {
+    let mut store = genesis_store();
+    let [_, b, _] = chain_of_three(&store);
+    store.on_block(b).unwrap();
+
+    tick_to(&mut store, 2 + ORPHAN_EXPIRY_SLOTS, 0);
+    assert_eq!(store.orphans.len(), 1);
+    tick_to(&mut store, 3 + ORPHAN_EXPIRY_SLOTS, 0);
+    assert!(store.orphans.is_empty());
+}
+
+#[test]
+fn test_full_orphan_pool_drops_earliest_arrival() {
+    let store = genesis_store();
+    let [a, b, c] = chain_of_three(&store);
+    let mut pool = OrphanPool::with_capacity(2);
+
+    assert!(pool.insert(b.clone(), 0, Slot(0)));
+    assert!(!pool.insert(b.clone(), 5, Slot(0)));
+    assert!(pool.insert(c.clone(), 1, Slot(0)));
+    assert!(pool.insert(a.clone(), 2, Slot(0)));
+    assert_eq!(pool.len(), 2);
+    assert!(!pool.contains(&root_of(&b)));
+    assert!(pool.contains(&root_of(&a)));
+
+    assert!(pool.take_children(&b.message.parent_root).is_empty());
+    assert_eq!(pool.take_children(&root_of(&b)).len(), 1);
+    assert_eq!(pool.len(), 1);
+    assert_eq!(pool.prune_up_to(Slot(3)), 1);
+    assert!(pool.is_empty());
+}
+
+#[test]
+fn test_far_future_orphans_are_refused() {
+    let mut store = genesis_store();
+    let [_, b, _] = chain_of_three(&store);
+    // Orphans aren't verified until their parent shows up, so the stale signature doesn't matter
+    let mut far = b.clone();
+    far.message.slot = Slot(ORPHAN_EXPIRY_SLOTS + 1);
+
+    let mut pool = OrphanPool::default();
+    assert!(!pool.insert(far.clone(), 0, Slot(0)));
+    assert!(pool.insert(far.clone(), 0, Slot(1)));
+
+    store.on_block(far).unwrap();
+    assert!(store.orphans.is_empty());
+    store.on_block(b).unwrap();
+    assert_eq!(store.orphans.len(), 1);
+}
@@ -0,0 +1,119 @@
+// tests/proposer_boost.rs
+use containers::Slot;
+use fork_choice::Store;
+
+mod common;
+use common::*;
+
+const NUM_VALIDATORS: u64 = 10;
+
+/// Genesis and block `a` at slot 1, backed by `a_votes` known votes by the start of slot 2.
+fn store_with_voted_block(a_votes: u64) -> (Store, containers::Bytes32) {
+    let mut store = genesis_store_with(NUM_VALIDATORS);
+    let genesis_root = store.head;
+    tick_to(&mut store, 1, 0);
+    let a = add_block(&mut store, genesis_root, 1, vec![]);
+    let votes: Vec<_> = (0..a_votes).map(|v| head_vote(&store, v, 1, a)).collect();
+    deliver_votes(&mut store, 1, &votes);
+    tick_to(&mut store, 2, 0);
+    (store, a)
+}
+
+#[test]
+fn test_timely_block_is_boosted_over_voted_sibling() {
+    let (mut store, a) = store_with_voted_block(3);
+    let genesis_root = store.blocks[&a].parent_root;
+    assert_eq!(store.get_head(), a);
+
+    // Boost is 40% of 10 validators, outweighing the 3 votes for `a`
+    let b = add_block(&mut store, genesis_root, 2, vec![]);
+    assert!(store.is_timely(&b));
+    assert_eq!(store.proposer_boost_root, Some(b));
+    assert_eq!(store.get_head(), b);
+}
+
+#[test]
+fn test_late_block_is_not_boosted() {
+    let (mut store, a) = store_with_voted_block(3);
+    let genesis_root = store.blocks[&a].parent_root;
+
+    tick_to(&mut store, 2, 1);
+    let b = add_block(&mut store, genesis_root, 2, vec![]);
+    assert!(!store.is_timely(&b));
+    assert_eq!(store.proposer_boost_root, None);
+    assert_eq!(store.get_head(), a);
+}
+
+#[test]
+fn test_boost_expires_at_next_slot() {
+    let (mut store, a) = store_with_voted_block(3);
+    let genesis_root = store.blocks[&a].parent_root;
+    let b = add_block(&mut store, genesis_root, 2, vec![]);
+    assert_eq!(store.get_head(), b);
+
+    tick_to(&mut store, 3, 0);
+    assert_eq!(store.proposer_boost_root, None);
+    assert_eq!(store.get_head(), a);
+}
+
+#[test]
+fn test_block_from_earlier_slot_is_not_boosted() {
+    let mut store = genesis_store_with(NUM_VALIDATORS);
+    let genesis_root = store.head;
+    tick_to(&mut store, 2, 0);
+    let a = add_block(&mut store, genesis_root, 1, vec![]);
+    assert!(!store.is_timely(&a));
+    assert_eq!(store.proposer_boost_root, None);
+}
+
+#[test]
+fn test_only_first_timely_block_is_boosted() {
+    let (mut store, a) = store_with_voted_block(0);
+    let genesis_root = store.blocks[&a].parent_root;
+    let b = add_block(&mut store, a, 2, vec![]);
+    let c = add_block(&mut store, genesis_root, 2, vec![]);
+    assert!(store.is_timely(&c));
+    assert_eq!(store.proposer_boost_root, Some(b));
+    assert_eq!(store.get_head(), b);
+}
+
+#[test]
+fn test_proposer_reorgs_weak_late_head() {
+    let (mut store, a) = store_with_voted_block(8);
+    tick_to(&mut store, 2, 2);
+    let b = add_block(&mut store, a, 2, vec![]);
+    assert_eq!(store.get_head(), b);
+
+    tick_to(&mut store, 3, 0);
+    assert_eq!(store.get_weight(&b), 0);
+    assert_eq!(store.get_weight(&a), 8);
+    assert_eq!(store.get_proposer_head(Slot(3)), a);
+    // Two slots later the head is no longer the previous slot's block
+    assert_eq!(store.get_proposer_head(Slot(4)), b);
+}
+
+#[test]
+fn test_proposer_keeps_timely_or_supported_head() {
+    // Timely head
+    let (mut store, a) = store_with_voted_block(8);
+    let b = add_block(&mut store, a, 2, vec![]);
+    tick_to(&mut store, 3, 0);
+    assert_eq!(store.get_proposer_head(Slot(3)), b);
+
+    // Late head with 20% of the votes
+    let (mut store, a) = store_with_voted_block(8);
+    tick_to(&mut store, 2, 1);
+    let b = add_block(&mut store, a, 2, vec![]);
+    let votes: Vec<_> = (0..2).map(|v| head_vote(&store, v, 2, b)).collect();
+    deliver_votes(&mut store, 2, &votes);
+    tick_to(&mut store, 3, 0);
+    assert_eq!(store.get_weight(&b), 2);
+    assert_eq!(store.get_proposer_head(Slot(3)), b);
+
+    // Late head on a weakly supported parent
+    let (mut store, a) = store_with_voted_block(5);
+    tick_to(&mut store, 2, 1);
+    let b = add_block(&mut store, a, 2, vec![]);
+    tick_to(&mut store, 3, 0);
+    assert_eq!(store.get_proposer_head(Slot(3)), b);
+}
@@ -0,0 +1,115 @@
+// tests/prune.rs
+use containers::{Bytes32, Checkpoint, Slot};
+use fork_choice::{BlockImport, Store};
+
+mod common;
+use common::*;
+
+/// Canonical chain genesis <- a <- b <- c, with votes in `b` and `c` finalizing `a`,
+/// plus a fork genesis <- x <- y at later slots, so no proposer signs two blocks for one slot.
+/// Returns `[genesis, a, b, c, x, y]`.
+fn finalizing_store() -> (Store, [Bytes32; 6]) {
+    let mut store = genesis_store();
+    let genesis_root = store.head;
+    let x = add_block(&mut store, genesis_root, 4, vec![]);
+    let y = add_block(&mut store, x, 5, vec![]);
+    let a = add_block(&mut store, genesis_root, 1, vec![]);
+
+    let genesis_checkpoint = checkpoint(&store, genesis_root);
+    let a_checkpoint = checkpoint(&store, a);
+    let votes = (0..2).map(|v| vote(v, 2, a_checkpoint.clone(), a_checkpoint.clone(), genesis_checkpoint.clone())).collect();
+    let b = add_block(&mut store, a, 2, votes);
+
+    let b_checkpoint = checkpoint(&store, b);
+    let votes = (0..2).map(|v| vote(v, 3, b_checkpoint.clone(), b_checkpoint.clone(), a_checkpoint.clone())).collect();
+    let c = add_block(&mut store, b, 3, votes);
+
+    (store, [genesis_root, a, b, c, x, y])
+}
+
+#[test]
+fn test_on_finalized_prunes_non_descendants() {
+    let (mut store, [genesis_root, a, b, c, x, y]) = finalizing_store();
+    assert_eq!(store.latest_finalized, Checkpoint { root: a, slot: Slot(1) });
+    assert_eq!(store.latest_justified, Checkpoint { root: b, slot: Slot(2) });
+
+    let pruned = store.on_finalized();
+    assert_eq!(pruned.len(), 3);
+    assert_eq!(pruned[0], genesis_root);
+    assert!(pruned.contains(&x) && pruned.contains(&y));
+
+    let mut remaining: Vec<_> = store.blocks.keys().copied().collect();
+    remaining.sort();
+    let mut expected = vec![a, b, c];
+    expected.sort();
+    assert_eq!(remaining, expected);
+    assert_eq!(store.states.len(), 3);
+    assert_eq!(store.get_head(), c);
+}
+
+#[test]
+fn test_on_finalized_drops_votes_for_pruned_blocks() {
+    let (mut store, [_, _, _, c, _, y]) = finalizing_store();
+    let votes = vec![head_vote(&store, 0, 4, c), head_vote(&store, 2, 4, y)];
+    tick_to(&mut store, 4, 1);
+    // Voter 2's vote for `y` is valid while `y` is still known
+    for vote in &votes {
+        store.on_attestation(vote).unwrap();
+    }
+
+    store.on_finalized();
+    assert_eq!(store.latest_new_votes.len(), 1);
+    assert_eq!(store.new_vote_arrivals.len(), 1);
+    assert!(store.latest_new_votes.values().all(|vote| vote.head.root == c));
+    assert!(store.latest_known_votes.values().all(|vote| store.blocks.contains_key(&vote.head.root)));
+}
+
+#[test]
+fn test_on_finalized_drops_late_messages_for_pruned_blocks() {
+    let (mut store, [_, _, _, c, _, y]) = finalizing_store();
+    tick_to(&mut store, 6, 3);
+    for parent in [c, y] {
+        let block = build_block(&store, parent, 6, vec![]);
+        assert!(matches!(store.on_block(block), Ok(BlockImport::Late(_))));
+    }
+    store.on_attestation(&head_vote(&store, 0, 6, c)).unwrap();
+    store.on_attestation(&head_vote(&store, 2, 6, y)).unwrap();
+
+    store.on_finalized();
+    assert_eq!(store.late_blocks.len(), 1);
+    assert!(store.late_blocks.values().all(|(signed_block, _)| signed_block.message.parent_root == c));
+    assert_eq!(store.late_votes.len(), 1);
+    assert!(store.late_votes.values().all(|signed_vote| signed_vote.data.head.root == c));
+}
+
+#[test]
+fn test_on_finalized_is_idempotent() {
+    let (mut store, _) = finalizing_store();
+    assert_eq!(store.on_finalized().len(), 3);
+    assert!(store.on_finalized().is_empty());
+
+    // Blocks can still be imported on top of the pruned tree
+    let head = store.get_head();
+    let next = add_block(&mut store, head, 6, vec![]);
+    assert_eq!(store.get_head(), next);
+}
+
+#[test]
+fn test_on_finalized_without_finality_keeps_everything() {
+    let mut store = genesis_store();
+    let genesis_root = store.head;
+    let a = add_block(&mut store, genesis_root, 1, vec![]);
+    add_block(&mut store, genesis_root, 2, vec![]);
+    add_block(&mut store, a, 3, vec![]);
+
+    assert!(store.on_finalized().is_empty());
+    assert_eq!(store.blocks.len(), 4);
+}
+
+#[test]
+fn test_on_finalized_resets_pruned_checkpoints() {
+    let (mut store, [_, a, _, _, x, _]) = finalizing_store();
+    store.safe_target = checkpoint(&store, x);
+    store.on_finalized();
+    assert_eq!(store.safe_target, Checkpoint { root: a, slot: Slot(1) });
+}
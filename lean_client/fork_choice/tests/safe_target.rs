@@ -0,0 +1,76 @@
+// tests/safe_target.rs
+use containers::{Checkpoint, Slot};
+use fork_choice::supermajority_threshold;
+use rstest::rstest;
+
+mod common;
+use common::*;
+
+#[rstest]
+#[case(1, 1)]
+#[case(3, 2)]
+#[case(4, 3)]
+#[case(10, 7)]
+fn test_supermajority_threshold(#[case] num_validators: u64, #[case] expected: usize) {
+    assert_eq!(supermajority_threshold(num_validators), expected);
+    // Same boundary as `State::process_attestations`
+    assert!(3 * expected as u64 >= 2 * num_validators);
+    assert!(3 * (expected as u64 - 1) < 2 * num_validators);
+}
+
+#[test]
+fn test_safe_target_starts_at_anchor() {
+    let store = genesis_store();
+    assert_eq!(store.get_safe_target(), Checkpoint { root: store.head, slot: Slot(0) });
+}
+
+#[test]
+fn test_safe_target_stops_where_support_drops_below_two_thirds() {
+    let mut store = genesis_store();
+    let genesis_root = store.head;
+    let a = add_block(&mut store, genesis_root, 1, vec![]);
+    let a2 = add_block(&mut store, a, 2, vec![]);
+    let b = add_block(&mut store, genesis_root, 3, vec![]);
+
+    // `a` is backed by validators 0 and 1, `a2` only by validator 0
+    tick_to(&mut store, 3, 1);
+    for (v, root) in [(0, a2), (1, a), (2, b)] {
+        store.on_attestation(&head_vote(&store, v, 3, root)).unwrap();
+    }
+    tick_to(&mut store, 3, 2);
+
+    assert_eq!(store.get_safe_target(), Checkpoint { root: a, slot: Slot(1) });
+    // The head follows plain LMD-GHOST and still sits on the heavier branch
+    tick_to(&mut store, 3, 3);
+    assert_eq!(store.get_head(), a2);
+}
+
+#[test]
+fn test_safe_target_uses_known_votes() {
+    let mut store = genesis_store();
+    let genesis_root = store.head;
+    let a = add_block(&mut store, genesis_root, 1, vec![]);
+
+    let votes: Vec<_> = (0..3).map(|v| head_vote(&store, v, 1, a)).collect();
+    deliver_votes(&mut store, 1, &votes);
+    assert!(store.latest_new_votes.is_empty());
+
+    // No new votes in slot 2: the promoted ones still back `a`
+    tick_to(&mut store, 2, 2);
+    assert_eq!(store.get_safe_target().root, a);
+}
+
+#[test]
+fn test_safe_target_can_be_used_as_vote_target() {
+    let mut store = genesis_store();
+    let genesis_root = store.head;
+    let a = add_block(&mut store, genesis_root, 1, vec![]);
+    let votes: Vec<_> = (0..2).map(|v| head_vote(&store, v, 1, a)).collect();
+    deliver_votes(&mut store, 1, &votes);
+
+    tick_to(&mut store, 2, 1);
+    let target = store.get_safe_target();
+    let source = store.latest_justified.clone();
+    let head = checkpoint(&store, store.get_head());
+    store.on_attestation(&vote(2, 2, head, target, source)).unwrap();
+}
@@ -0,0 +1,227 @@
+// tests/store.rs
+use containers::{
+    block::hash_tree_root, signature::SignatureError, Bytes32, Checkpoint, PublicKey, Signature, SignatureScheme, Slot,
+    StateTransitionError,
+};
+use fork_choice::{BlockImport, ForkChoiceError, Store};
+
+mod common;
+use common::*;
+
+#[test]
+fn test_new_store_starts_at_anchor() {
+    let (state, block) = genesis();
+    let anchor_root = hash_tree_root(&block);
+    let store = Store::new(state, block).unwrap();
+
+    assert_eq!(store.get_head(), anchor_root);
+    assert_eq!(store.latest_justified, Checkpoint { root: anchor_root, slot: Slot(0) });
+    assert_eq!(store.latest_finalized, store.latest_justified);
+}
+
+#[test]
+fn test_genesis_store_agrees_with_genesis_state_checkpoints() {
+    let (state, _) = genesis();
+    let anchor = state.anchor_checkpoint();
+    let mut store = Store::from_genesis(state);
+    assert_eq!(store.latest_justified, anchor);
+    assert_eq!(store.latest_finalized, anchor);
+
+    // The first block's state points its checkpoints at the same anchor instead of zero roots
+    let a = add_block(&mut store, anchor.root, 1, vec![]);
+    assert_eq!(store.states[&a].latest_justified, anchor);
+    assert_eq!(store.states[&a].latest_finalized, anchor);
+}
+
+#[test]
+fn test_new_store_rejects_inconsistent_anchor() {
+    let (state, mut block) = genesis();
+    block.state_root = Bytes32::default();
+    assert!(matches!(Store::new(state, block), Err(ForkChoiceError::AnchorStateRootMismatch { .. })));
+}
+
+#[test]
+fn test_head_follows_chain_without_votes() {
+    let mut store = genesis_store();
+    let genesis_root = store.head;
+    let a = add_block(&mut store, genesis_root, 1, vec![]);
+    let b = add_block(&mut store, a, 2, vec![]);
+    assert_eq!(store.get_head(), b);
+    assert_eq!(store.blocks.len(), 3);
+    assert_eq!(store.states.len(), 3);
+}
+
+#[test]
+fn test_head_follows_vote_weight_on_fork() {
+    let mut store = genesis_store();
+    let genesis_root = store.head;
+    let a = add_block(&mut store, genesis_root, 1, vec![]);
+    let b = add_block(&mut store, genesis_root, 2, vec![]);
+    // Without votes the later block wins the tie
+    assert_eq!(store.get_head(), b);
+
+    let votes = [head_vote(&store, 0, 2, a), head_vote(&store, 1, 2, a), head_vote(&store, 2, 2, b)];
+    deliver_votes(&mut store, 2, &votes);
+    assert_eq!(store.get_head(), a);
+}
+
+#[test]
+fn test_newer_vote_replaces_older_one() {
+    let mut store = genesis_store();
+    let genesis_root = store.head;
+    let a = add_block(&mut store, genesis_root, 1, vec![]);
+    let b = add_block(&mut store, genesis_root, 2, vec![]);
+
+    // An older vote from the same validator is ignored
+    let votes = [head_vote(&store, 0, 3, a), head_vote(&store, 0, 2, b)];
+    deliver_votes(&mut store, 3, &votes);
+    assert_eq!(store.get_head(), a);
+
+    let vote = head_vote(&store, 0, 4, b);
+    deliver_votes(&mut store, 4, &[vote]);
+    assert_eq!(store.get_head(), b);
+    assert_eq!(store.latest_known_votes.len(), 1);
+}
+
+#[test]
+fn test_on_block_queues_unknown_parent() {
+    let mut store = genesis_store();
+    let block = build_block(&store, store.head, 1, vec![]);
+    let mut orphan = block.clone();
+    orphan.message.parent_root = Bytes32::default();
+
+    assert_eq!(store.on_block(orphan.clone()), Ok(BlockImport::Orphaned(hash_tree_root(&orphan.message))));
+    assert_eq!(store.blocks.len(), 1);
+    assert_eq!(store.orphans.len(), 1);
+}
+
+#[test]
+fn test_on_block_rejects_invalid_block() {
+    let mut store = genesis_store();
+    let mut block = build_block(&store, store.head, 1, vec![]).message;
+    block.state_root = Bytes32::default();
+
+    let result = store.on_block(sign_block(block));
+    assert!(matches!(result, Err(ForkChoiceError::StateTransition(StateTransitionError::StateRootMismatch { .. }))));
+    assert_eq!(store.blocks.len(), 1);
+}
+
+#[test]
+fn test_on_attestation_rejects_unknown_or_inconsistent_checkpoints() {
+    let mut store = genesis_store();
+    let genesis_root = store.head;
+    let a = add_block(&mut store, genesis_root, 1, vec![]);
+    tick_to(&mut store, 1, 1);
+
+    let unknown = Checkpoint { root: Bytes32::default(), slot: Slot(1) };
+    let source = store.latest_justified.clone();
+    let result = store.on_attestation(&vote(0, 1, unknown.clone(), unknown, source.clone()));
+    assert_eq!(result, Err(ForkChoiceError::UnknownBlock(Bytes32::default())));
+
+    let wrong_slot = Checkpoint { root: a, slot: Slot(5) };
+    let result = store.on_attestation(&vote(0, 1, wrong_slot.clone(), wrong_slot, source));
+    assert!(matches!(result, Err(ForkChoiceError::CheckpointSlotMismatch { .. })));
+    assert!(store.latest_new_votes.is_empty());
+}
+
+#[test]
+fn test_head_stays_within_justified_subtree() {
+    let mut store = genesis_store();
+    let genesis_root = store.head;
+    let a = add_block(&mut store, genesis_root, 1, vec![]);
+
+    // Two of three validators justify `a`; the votes are packed into `b`
+    let source = store.latest_justified.clone();
+    let target = checkpoint(&store, a);
+    let votes = (0..2).map(|v| vote(v, 2, target.clone(), target.clone(), source.clone())).collect();
+    let b = add_block(&mut store, a, 2, votes);
+    assert_eq!(store.latest_justified, target);
+
+    // A competing branch off genesis gets every validator's latest vote but isn't under the justified root
+    let c = add_block(&mut store, genesis_root, 3, vec![]);
+    let votes: Vec<_> = (0..3).map(|v| head_vote(&store, v, 3, c)).collect();
+    deliver_votes(&mut store, 3, &votes);
+    assert_eq!(store.latest_known_votes.len(), 3);
+    assert_eq!(store.get_head(), b);
+}
+
+#[test]
+fn test_block_vote_clears_pending_gossip_vote() {
+    let mut store = genesis_store();
+    let genesis_root = store.head;
+    let a = add_block(&mut store, genesis_root, 1, vec![]);
+    tick_to(&mut store, 1, 1);
+
+    let gossip = head_vote(&store, 0, 1, a);
+    store.on_attestation(&gossip).unwrap();
+    assert_eq!(store.new_vote_arrivals.len(), 1);
+
+    add_block(&mut store, a, 2, vec![gossip]);
+    assert!(store.latest_new_votes.is_empty());
+    assert!(store.new_vote_arrivals.is_empty());
+    assert_eq!(store.latest_known_votes[&containers::ValidatorIndex(0)].head.root, a);
+}
+
+#[test]
+fn test_on_attestation_rejects_bad_signature() {
+    let mut store = genesis_store();
+    let genesis_root = store.head;
+    let a = add_block(&mut store, genesis_root, 1, vec![]);
+    tick_to(&mut store, 1, 1);
+
+    // Signed by validator 0 but claimed for validator 1
+    let mut forged = head_vote(&store, 0, 1, a);
+    forged.data.validator_id = containers::Uint64(1);
+    let result = store.on_attestation(&forged);
+    assert_eq!(
+        result,
+        Err(ForkChoiceError::VoteSignature(StateTransitionError::InvalidVoteSignature {
+            validator_id: containers::ValidatorIndex(1),
+            slot: Slot(1),
+        }))
+    );
+    assert!(store.latest_new_votes.is_empty());
+}
+
+/// Accepts every signature, to tell which scheme the store verifies with.
+#[derive(Clone, Debug)]
+struct AcceptAll;
+
+impl SignatureScheme for AcceptAll {
+    type SecretKey = ();
+
+    fn sign(&self, _: &(), _: u64, _: Bytes32) -> Result<Signature, SignatureError> {
+        Ok(Signature::default())
+    }
+
+    fn verify(&self, _: &PublicKey, _: u64, _: Bytes32, _: &Signature) -> bool {
+        true
+    }
+}
+
+#[test]
+fn test_store_verifies_with_its_scheme() {
+    let (state, block) = genesis();
+    let genesis_root = hash_tree_root(&block);
+    let mut xmss_store = genesis_store();
+    let mut store = Store::with_scheme(state, block, AcceptAll).unwrap();
+
+    let mut unsigned = build_block(&xmss_store, genesis_root, 1, vec![]);
+    unsigned.signature = Signature::default();
+    assert!(matches!(xmss_store.on_block(unsigned.clone()), Err(ForkChoiceError::StateTransition(_))));
+    let a = store.on_block(unsigned).unwrap().root();
+
+    // Late blocks and gossip votes are checked with the same scheme
+    tick_to(&mut store, 2, 3);
+    tick_to(&mut xmss_store, 2, 3);
+    let mut late = build_block(&xmss_store, genesis_root, 2, vec![]);
+    late.signature = Signature::default();
+    assert!(matches!(store.on_block(late), Ok(BlockImport::Late(_))));
+
+    let mut forged = head_vote(&xmss_store, 0, 1, genesis_root);
+    forged.data.head = checkpoint(&store, a);
+    forged.data.validator_id = containers::Uint64(1);
+    assert_eq!(store.on_attestation(&forged), Ok(()));
+    assert_eq!(store.late_votes.len(), 1);
+    assert!(matches!(xmss_store.on_attestation(&forged), Err(ForkChoiceError::VoteSignature(_))));
+}
@@ -0,0 +1,172 @@
+// tests/view_freeze.rs
+use chain::config::{SLOT_DURATION_MS, VOTE_DUE_BPS};
+use containers::{block::hash_tree_root, Bytes32, StateTransitionError, ValidatorIndex};
+use fork_choice::{BlockImport, ForkChoiceError, MAX_LATE_BLOCKS};
+
+mod common;
+use common::*;
+
+#[test]
+fn test_view_freezes_at_three_quarters_of_slot() {
+    let mut store = genesis_store();
+    for interval in 0..3 {
+        tick_to(&mut store, 1, interval);
+        assert!(!store.is_view_frozen());
+    }
+    tick_to(&mut store, 1, 3);
+    assert!(store.is_view_frozen());
+    tick_to(&mut store, 2, 0);
+    assert!(!store.is_view_frozen());
+}
+
+#[test]
+fn test_late_block_is_imported_when_next_slot_begins() {
+    let mut store = genesis_store();
+    let genesis_root = store.head;
+    tick_to(&mut store, 1, 3);
+
+    let block = build_block(&store, genesis_root, 1, vec![]);
+    let root = hash_tree_root(&block.message);
+    assert_eq!(store.on_block(block.clone()), Ok(BlockImport::Late(root)));
+    assert_eq!(store.on_block(block), Ok(BlockImport::Late(root)));
+    assert!(!store.blocks.contains_key(&root));
+    assert_eq!(store.late_blocks.len(), 1);
+    assert_eq!(store.get_head(), genesis_root);
+    assert_eq!(store.get_frozen_head(), genesis_root);
+
+    tick_to(&mut store, 2, 0);
+    assert!(store.late_blocks.is_empty());
+    assert!(store.blocks.contains_key(&root));
+    assert_eq!(store.get_head(), root);
+    // Arrived in its own slot, but after the reorg cutoff
+    assert!(!store.is_timely(&root));
+}
+
+#[test]
+fn test_late_vote_is_accepted_when_next_slot_begins() {
+    let mut store = genesis_store();
+    let genesis_root = store.head;
+    let a = add_block(&mut store, genesis_root, 1, vec![]);
+    let b = add_block(&mut store, genesis_root, 2, vec![]);
+
+    tick_to(&mut store, 2, 3);
+    store.on_attestation(&head_vote(&store, 0, 2, a)).unwrap();
+    assert!(store.latest_new_votes.is_empty());
+    assert_eq!(store.late_votes.len(), 1);
+
+    tick_to(&mut store, 3, 0);
+    assert!(store.late_votes.is_empty());
+    assert_eq!(store.latest_new_votes[&ValidatorIndex(0)].head.root, a);
+    // Counts once promoted, like any other gossip vote
+    assert_eq!(store.get_head(), b);
+    tick_to(&mut store, 3, 3);
+    assert_eq!(store.get_head(), a);
+}
+
+#[test]
+fn test_late_votes_keep_the_newest_per_validator() {
+    let mut store = genesis_store();
+    let genesis_root = store.head;
+    let a = add_block(&mut store, genesis_root, 1, vec![]);
+    let b = add_block(&mut store, a, 2, vec![]);
+    tick_to(&mut store, 2, 3);
+
+    let vote = head_vote(&store, 0, 2, b);
+    store.on_attestation(&vote).unwrap();
+    store.on_attestation(&vote).unwrap();
+    store.on_attestation(&head_vote(&store, 0, 1, a)).unwrap();
+    assert_eq!(store.late_votes.len(), 1);
+    assert_eq!(store.late_votes[&ValidatorIndex(0)], vote);
+
+    store.on_attestation(&head_vote(&store, 1, 2, b)).unwrap();
+    assert_eq!(store.late_votes.len(), 2);
+}
+
+#[test]
+fn test_late_vote_is_still_validated_on_arrival() {
+    let mut store = genesis_store();
+    let genesis_root = store.head;
+    let a = add_block(&mut store, genesis_root, 1, vec![]);
+    tick_to(&mut store, 1, 3);
+
+    let mut vote = head_vote(&store, 0, 1, a);
+    vote.data.head.root = containers::Bytes32::default();
+    assert!(store.on_attestation(&vote).is_err());
+    assert!(store.late_votes.is_empty());
+}
+
+#[test]
+fn test_frozen_head_is_head_at_freeze_point() {
+    let mut store = genesis_store();
+    let genesis_root = store.head;
+    let a = add_block(&mut store, genesis_root, 1, vec![]);
+    tick_to(&mut store, 1, 3);
+    assert_eq!(store.frozen_head, a);
+
+    // A competing block after the freeze doesn't change the voting view
+    let b = build_block(&store, genesis_root, 2, vec![]);
+    store.on_block(b).unwrap();
+    assert_eq!(store.get_frozen_head(), a);
+
+    // Before the freeze point the frozen head is the live head
+    tick_to(&mut store, 2, 1);
+    assert_eq!(store.get_frozen_head(), store.get_head());
+}
+
+#[test]
+fn test_late_block_is_checked_before_queueing() {
+    let mut store = genesis_store();
+    let genesis_root = store.head;
+    tick_to(&mut store, 1, 3);
+
+    let mut forged = build_block(&store, genesis_root, 1, vec![]);
+    forged.message.proposer_index = ValidatorIndex(2);
+    assert!(matches!(
+        store.on_block(forged),
+        Err(ForkChoiceError::StateTransition(StateTransitionError::InvalidBlockSignature { .. }))
+    ));
+
+    let mut orphan = build_block(&store, genesis_root, 1, vec![]);
+    orphan.message.parent_root = Bytes32::default();
+    let orphan = sign_block(orphan.message);
+    assert_eq!(store.on_block(orphan.clone()), Ok(BlockImport::Orphaned(hash_tree_root(&orphan.message))));
+    assert!(store.late_blocks.is_empty());
+    assert_eq!(store.orphans.len(), 1);
+}
+
+#[test]
+fn test_late_blocks_are_capped_until_next_slot() {
+    let mut store = genesis_store();
+    let genesis_root = store.head;
+    tick_to(&mut store, 1, 3);
+
+    // Distinct, correctly signed blocks: only the state root differs
+    let block = build_block(&store, genesis_root, 1, vec![]).message;
+    let variant = |i: u64| {
+        let mut block = block.clone();
+        block.state_root = Bytes32(containers::ssz::H256::from_low_u64_be(i + 1));
+        sign_block(block)
+    };
+    for i in 0..MAX_LATE_BLOCKS as u64 {
+        assert!(matches!(store.on_block(variant(i)), Ok(BlockImport::Late(_))));
+    }
+    assert_eq!(store.on_block(variant(MAX_LATE_BLOCKS as u64)), Err(ForkChoiceError::LateBlocksFull));
+
+    tick_to(&mut store, 2, 0);
+    assert!(store.late_blocks.is_empty());
+    assert!(matches!(store.on_block(build_block(&store, genesis_root, 2, vec![])), Ok(BlockImport::Imported(_))));
+}
+
+#[test]
+fn test_votes_due_before_the_freeze_see_the_live_head() {
+    // Votes are due at interval 2 and the view only freezes at interval 3, so a vote cast on
+    // time follows everything received so far; the frozen view only holds back later arrivals
+    assert!(VOTE_DUE_BPS.of(SLOT_DURATION_MS) < chain::config::VIEW_FREEZE_CUTOFF_BPS.of(SLOT_DURATION_MS));
+
+    let mut store = genesis_store();
+    let genesis_root = store.head;
+    tick_to(&mut store, 1, 2);
+    let a = add_block(&mut store, genesis_root, 1, vec![]);
+    assert!(!store.is_view_frozen());
+    assert_eq!(store.get_frozen_head(), a);
+}
@@ -0,0 +1,85 @@
+// tests/vote_target.rs
+use chain::config::JUSTIFICATION_LOOKBACK_SLOTS;
+use containers::{Bytes32, Checkpoint, Slot};
+use fork_choice::{get_vote_target, Store};
+
+mod common;
+use common::*;
+
+/// Genesis followed by one block per slot up to `last_slot`; returns the roots by slot.
+fn linear_chain(store: &mut Store, last_slot: u64) -> Vec<Bytes32> {
+    let mut roots = vec![store.head];
+    for slot in 1..=last_slot {
+        let parent = *roots.last().unwrap();
+        roots.push(add_block(store, parent, slot, vec![]));
+    }
+    roots
+}
+
+#[test]
+fn test_vote_target_is_head_when_head_is_safe() {
+    let mut store = genesis_store();
+    let roots = linear_chain(&mut store, 1);
+    store.safe_target = checkpoint(&store, roots[1]);
+    assert_eq!(get_vote_target(&store), Checkpoint { root: roots[1], slot: Slot(1) });
+}
+
+#[test]
+fn test_vote_target_starts_at_frozen_head() {
+    let mut store = genesis_store();
+    let genesis_root = store.head;
+    let x = add_block(&mut store, genesis_root, 1, vec![]);
+    let y = add_block(&mut store, genesis_root, 2, vec![]);
+    tick_to(&mut store, 2, 3);
+    assert!(store.is_view_frozen());
+
+    // The live head moves away from the one votes are cast for after the freeze
+    let frozen = store.get_frozen_head();
+    store.head = if frozen == x { y } else { x };
+    store.safe_target = checkpoint(&store, frozen);
+
+    assert_eq!(get_vote_target(&store), checkpoint(&store, frozen));
+}
+
+#[test]
+fn test_vote_target_walks_back_at_most_lookback_slots() {
+    let mut store = genesis_store();
+    let roots = linear_chain(&mut store, 5);
+    assert_eq!(store.head, roots[5]);
+    assert_eq!(store.safe_target.slot, Slot(0));
+
+    let target = get_vote_target(&store);
+    let expected_slot = 5 - JUSTIFICATION_LOOKBACK_SLOTS;
+    assert_eq!(target, Checkpoint { root: roots[expected_slot as usize], slot: Slot(expected_slot) });
+}
+
+#[test]
+fn test_vote_target_stops_at_safe_target() {
+    let mut store = genesis_store();
+    let roots = linear_chain(&mut store, 5);
+    store.safe_target = checkpoint(&store, roots[4]);
+    assert_eq!(get_vote_target(&store).root, roots[4]);
+}
+
+#[test]
+fn test_vote_target_skips_unjustifiable_slots() {
+    let mut store = genesis_store();
+    let roots = linear_chain(&mut store, 10);
+
+    // Lookback lands on slot 7, which isn't justifiable after slot 0; slot 6 (pronic) is
+    assert!(!Slot(7).is_justifiable_after(Slot(0)));
+    let target = get_vote_target(&store);
+    assert_eq!(target, Checkpoint { root: roots[6], slot: Slot(6) });
+    assert!(target.slot.is_justifiable_after(store.latest_finalized.slot));
+}
+
+#[test]
+fn test_vote_target_is_relative_to_latest_finalized() {
+    let mut store = genesis_store();
+    let roots = linear_chain(&mut store, 10);
+    store.latest_finalized = checkpoint(&store, roots[3]);
+    store.safe_target = checkpoint(&store, roots[3]);
+
+    // Slot 7 is 4 slots after finalization, a perfect square
+    assert_eq!(get_vote_target(&store).slot, Slot(7));
+}
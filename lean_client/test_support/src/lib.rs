@@ -0,0 +1,57 @@
+//! Deterministic validator keys and signing helpers shared by the test suites of every crate.
+use chain::config::{INTERVALS_PER_SLOT, SECONDS_PER_INTERVAL};
+use containers::{
+    block::{hash_tree_root, Block, SignedBlock},
+    signature::{block_signing_epoch, vote_signing_epoch, PublicKey, SignatureScheme, Xmss, XmssSecretKey},
+    Bytes32, Checkpoint, SignedVote, Slot, Uint64, Vote,
+};
+use fork_choice::Store;
+use std::sync::OnceLock;
+
+/// Keys are generated for this many validators; tests may use any prefix of them.
+pub const MAX_TEST_VALIDATORS: u64 = 10;
+/// Test keys sign for 2^6 epochs, i.e. slots 0 to 31.
+pub const TEST_KEY_TREE_HEIGHT: usize = 6;
+
+/// Deterministic key of a test validator, generated on first use in each test binary.
+pub fn test_key(validator: u64) -> &'static (PublicKey, XmssSecretKey) {
+    static KEYS: [OnceLock<(PublicKey, XmssSecretKey)>; MAX_TEST_VALIDATORS as usize] =
+        [const { OnceLock::new() }; MAX_TEST_VALIDATORS as usize];
+    KEYS[validator as usize].get_or_init(|| {
+        let mut seed = [0u8; 32];
+        seed[..8].copy_from_slice(&validator.to_le_bytes());
+        Xmss.generate_key(seed, TEST_KEY_TREE_HEIGHT)
+    })
+}
+
+/// Public keys of the first `count` test validators.
+pub fn test_pubkeys(count: u64) -> Vec<PublicKey> {
+    (0..count).map(|validator| test_key(validator).0.clone()).collect()
+}
+
+/// Signs `block` with its proposer's test key.
+pub fn sign_block(block: Block) -> SignedBlock {
+    let secret_key = &test_key(block.proposer_index.0).1;
+    let signature = Xmss.sign(secret_key, block_signing_epoch(block.slot), hash_tree_root(&block));
+    SignedBlock { message: block, signature: signature.expect("slot within test key lifetime") }
+}
+
+/// Signs `vote` with its validator's test key.
+pub fn sign_vote(vote: Vote) -> SignedVote {
+    let secret_key = &test_key(vote.validator_id.0).1;
+    let signature = Xmss.sign(secret_key, vote_signing_epoch(vote.slot), hash_tree_root(&vote));
+    SignedVote { data: vote, signature: signature.expect("slot within test key lifetime") }
+}
+
+pub fn vote(validator: u64, slot: u64, head: Checkpoint, target: Checkpoint, source: Checkpoint) -> SignedVote {
+    sign_vote(Vote { validator_id: Uint64(validator), slot: Slot(slot), head, target, source })
+}
+
+pub fn checkpoint<S: SignatureScheme>(store: &Store<S>, root: Bytes32) -> Checkpoint {
+    Checkpoint { root, slot: store.blocks[&root].slot }
+}
+
+/// Ticks the store to `interval` of `slot` (genesis time is 0).
+pub fn tick_to<S: SignatureScheme>(store: &mut Store<S>, slot: u64, interval: u64) {
+    store.on_tick((slot * INTERVALS_PER_SLOT + interval) * SECONDS_PER_INTERVAL, false);
+}
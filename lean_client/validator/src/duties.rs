@@ -0,0 +1,102 @@
+use chain::config::{BasisPoint, INTERVALS_PER_SLOT, SECONDS_PER_INTERVAL, SLOT_DURATION_MS, VOTE_DUE_BPS};
+use containers::{ContainerConfig, Slot, ValidatorIndex};
+use std::collections::BTreeSet;
+
+/// Something a local validator has to do now.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Duty {
+    /// Build and publish the slot's block; due at interval 0.
+    Propose { slot: Slot, validator: ValidatorIndex },
+    /// Vote for the current head; due at `VOTE_DUE_BPS` of the slot.
+    Vote { slot: Slot, validator: ValidatorIndex },
+}
+
+impl Duty {
+    pub fn slot(&self) -> Slot {
+        match self {
+            Self::Propose { slot, .. } | Self::Vote { slot, .. } => *slot,
+        }
+    }
+
+    pub fn validator(&self) -> ValidatorIndex {
+        match self {
+            Self::Propose { validator, .. } | Self::Vote { validator, .. } => *validator,
+        }
+    }
+}
+
+/// Interval of the slot in which `VOTE_DUE_BPS` falls.
+pub fn vote_due_interval() -> u64 {
+    interval_of(VOTE_DUE_BPS)
+}
+
+fn interval_of(cutoff: BasisPoint) -> u64 {
+    cutoff.of(SLOT_DURATION_MS) / (SECONDS_PER_INTERVAL * 1_000)
+}
+
+/// Tracks time for a set of local validators and emits their duties as the intervals come due.
+#[derive(Clone, Debug)]
+pub struct ValidatorClient {
+    config: ContainerConfig,
+    validators: BTreeSet<ValidatorIndex>,
+    /// Last interval since genesis whose duties were emitted; `None` before the first tick.
+    time: Option<u64>,
+}
+
+impl ValidatorClient {
+    pub fn new(config: ContainerConfig, validators: impl IntoIterator<Item = ValidatorIndex>) -> Self {
+        Self { config, validators: validators.into_iter().collect(), time: None }
+    }
+
+    pub fn validators(&self) -> &BTreeSet<ValidatorIndex> {
+        &self.validators
+    }
+
+    /// Returns false if the validator was already managed here.
+    pub fn add_validator(&mut self, validator: ValidatorIndex) -> bool {
+        self.validators.insert(validator)
+    }
+
+    pub fn remove_validator(&mut self, validator: &ValidatorIndex) -> bool {
+        self.validators.remove(validator)
+    }
+
+    /// The local validator proposing at `slot`, if any. Nobody proposes at genesis.
+    pub fn proposer(&self, slot: Slot) -> Option<ValidatorIndex> {
+        if slot == Slot(0) {
+            return None;
+        }
+        self.validators.iter().copied().find(|validator| self.config.is_proposer(slot, *validator))
+    }
+
+    /// Duties falling due at `interval` of `slot`, proposals first.
+    pub fn duties_at(&self, slot: Slot, interval: u64) -> Vec<Duty> {
+        let mut duties = Vec::new();
+        if interval == 0 {
+            duties.extend(self.proposer(slot).map(|validator| Duty::Propose { slot, validator }));
+        }
+        if interval == vote_due_interval() {
+            duties.extend(self.validators.iter().map(|&validator| Duty::Vote { slot, validator }));
+        }
+        duties
+    }
+
+    /// Advances to `time` (seconds since the UNIX epoch) and returns the duties that came due.
+    /// Duties of slots that were skipped over entirely are dropped, as they are too late to carry out.
+    pub fn on_tick(&mut self, time: u64) -> Vec<Duty> {
+        if time < self.config.genesis_time {
+            return Vec::new();
+        }
+        let target = (time - self.config.genesis_time) / SECONDS_PER_INTERVAL;
+        let first = match self.time {
+            Some(last) if last >= target => return Vec::new(),
+            Some(last) => (last + 1).max(target - target % INTERVALS_PER_SLOT),
+            None => target,
+        };
+        self.time = Some(target);
+
+        (first..=target)
+            .flat_map(|interval| self.duties_at(Slot(interval / INTERVALS_PER_SLOT), interval % INTERVALS_PER_SLOT))
+            .collect()
+    }
+}
@@ -1,14 +1,11 @@
-pub fn add(left: u64, right: u64) -> u64 {
-    left + right
-}
+//! Local validator duties: when to propose and vote, building what they publish and
+//! making sure it is safe to sign.
+pub mod duties;
+pub mod production;
+pub mod signer;
+pub mod slashing_protection;
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn it_works() {
-        let result = add(2, 2);
-        assert_eq!(result, 4);
-    }
-}
+pub use duties::{vote_due_interval, Duty, ValidatorClient};
+pub use production::{produce_block, produce_vote};
+pub use signer::{LocalSigner, ProtectedSigner, Signer, SignerError};
+pub use slashing_protection::{Interchange, SlashingProtection, SlashingProtectionError};
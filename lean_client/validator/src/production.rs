@@ -0,0 +1,62 @@
+use crate::signer::{ProtectedSigner, Signer, SignerError};
+use containers::{
+    block::hash_tree_root, Block, BlockBody, Bytes32, Checkpoint, SignatureScheme, SignedVote, Slot, Uint64, ValidatorIndex, Vote,
+};
+use containers::ssz::PersistentList as List;
+use fork_choice::{get_vote_target, ForkChoiceError, Store};
+use typenum::{Unsigned, U4096};
+
+/// Builds the block `proposer_index` publishes at `slot`, on top of the store's proposer head.
+///
+/// Packs the pooled votes that would count in the block's state, see `AttestationPool::select`;
+/// the same store always yields the same block. The returned block is unsigned; sign it with
+/// `ProtectedSigner::sign_block`.
+pub fn produce_block(store: &Store<impl SignatureScheme>, slot: Slot, proposer_index: ValidatorIndex) -> Result<Block, ForkChoiceError> {
+    let parent_root = store.get_proposer_head(slot);
+    let parent_state = store.states.get(&parent_root).ok_or(ForkChoiceError::UnknownParent(parent_root))?;
+    let state = parent_state.process_slots(slot)?;
+
+    let mut block = Block {
+        slot,
+        proposer_index,
+        parent_root,
+        state_root: Bytes32::default(),
+        body: BlockBody { attestations: List::default() },
+    };
+    // Votes are checked against the chain including the parent, as `process_attestations` sees it
+    let header_state = state.process_block_header(&block)?;
+
+    for signed_vote in store.attestation_pool.select(&header_state, U4096::USIZE) {
+        block.body.attestations.push(signed_vote).expect("at most U4096 votes are selected");
+    }
+
+    block.state_root = hash_tree_root(&state.process_block(&block)?);
+    Ok(block)
+}
+
+/// Builds and signs the vote `validator_id` casts at `slot`.
+///
+/// The head is the fork-choice head as of the view freeze, the target comes from
+/// `get_vote_target`, which walks back from that same head, and the source is the store's latest justified checkpoint, so the
+/// same store always yields the same vote. `signer` refuses votes that could get the
+/// validator slashed, e.g. a second, different vote in the same slot.
+pub fn produce_vote(
+    store: &Store<impl SignatureScheme>,
+    slot: Slot,
+    validator_id: ValidatorIndex,
+    signer: &mut ProtectedSigner<impl Signer>,
+) -> Result<SignedVote, SignerError> {
+    let head = store.get_frozen_head();
+    let pubkey = store.states[&head]
+        .validator_pubkey(validator_id)
+        .map_err(|_| SignerError::UnknownValidator(validator_id))?
+        .clone();
+    let data = Vote {
+        validator_id: Uint64(validator_id.0),
+        slot,
+        head: Checkpoint { root: head, slot: store.blocks[&head].slot },
+        target: get_vote_target(store),
+        source: store.latest_justified.clone(),
+    };
+    signer.sign_vote(&pubkey, data)
+}
@@ -0,0 +1,103 @@
+use crate::slashing_protection::{SlashingProtection, SlashingProtectionError};
+use containers::block::hash_tree_root;
+use containers::signature::{block_signing_epoch, vote_signing_epoch, SignatureError};
+use containers::{Block, Bytes32, PublicKey, Signature, SignatureScheme, SignedBlock, SignedVote, ValidatorIndex, Vote, Xmss};
+use std::collections::HashMap;
+
+/// Reason a local validator's message was not signed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SignerError {
+    UnknownValidator(ValidatorIndex),
+    Signature(SignatureError),
+    SlashingProtection(SlashingProtectionError),
+}
+
+impl std::fmt::Display for SignerError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnknownValidator(index) => write!(f, "No key for validator {}", index.0),
+            Self::Signature(err) => write!(f, "Signing failed: {}", err),
+            Self::SlashingProtection(err) => write!(f, "Refused to sign: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for SignerError {}
+
+impl From<SignatureError> for SignerError {
+    fn from(err: SignatureError) -> Self {
+        Self::Signature(err)
+    }
+}
+
+impl From<SlashingProtectionError> for SignerError {
+    fn from(err: SlashingProtectionError) -> Self {
+        Self::SlashingProtection(err)
+    }
+}
+
+/// Signs message roots on behalf of local validators, wherever their keys live.
+pub trait Signer {
+    fn sign(&self, validator: ValidatorIndex, epoch: u64, message: Bytes32) -> Result<Signature, SignerError>;
+}
+
+/// Secret keys held in memory.
+pub struct LocalSigner<S: SignatureScheme = Xmss> {
+    scheme: S,
+    keys: HashMap<ValidatorIndex, S::SecretKey>,
+}
+
+impl<S: SignatureScheme> LocalSigner<S> {
+    pub fn new(scheme: S) -> Self {
+        Self { scheme, keys: HashMap::new() }
+    }
+
+    /// Returns the key previously held for `validator`, if any.
+    pub fn insert(&mut self, validator: ValidatorIndex, secret_key: S::SecretKey) -> Option<S::SecretKey> {
+        self.keys.insert(validator, secret_key)
+    }
+
+    pub fn validators(&self) -> impl Iterator<Item = &ValidatorIndex> {
+        self.keys.keys()
+    }
+}
+
+impl<S: SignatureScheme> Signer for LocalSigner<S> {
+    fn sign(&self, validator: ValidatorIndex, epoch: u64, message: Bytes32) -> Result<Signature, SignerError> {
+        let secret_key = self.keys.get(&validator).ok_or(SignerError::UnknownValidator(validator))?;
+        Ok(self.scheme.sign(secret_key, epoch, message)?)
+    }
+}
+
+/// Signs blocks and votes only after `SlashingProtection` has allowed and recorded them.
+///
+/// This is how validators should sign; the wrapped [`Signer`] signs any root it is given.
+pub struct ProtectedSigner<S: Signer = LocalSigner> {
+    signer: S,
+    protection: SlashingProtection,
+}
+
+impl<S: Signer> ProtectedSigner<S> {
+    pub fn new(signer: S, protection: SlashingProtection) -> Self {
+        Self { signer, protection }
+    }
+
+    pub fn protection(&self) -> &SlashingProtection {
+        &self.protection
+    }
+
+    /// Signs `block` as its proposer, whose registered key is `pubkey`.
+    pub fn sign_block(&mut self, pubkey: &PublicKey, block: Block) -> Result<SignedBlock, SignerError> {
+        self.protection.check_and_record_block(pubkey, &block)?;
+        let signature = self.signer.sign(block.proposer_index, block_signing_epoch(block.slot), hash_tree_root(&block))?;
+        Ok(SignedBlock { message: block, signature })
+    }
+
+    /// Signs `vote` as its validator, whose registered key is `pubkey`.
+    pub fn sign_vote(&mut self, pubkey: &PublicKey, vote: Vote) -> Result<SignedVote, SignerError> {
+        self.protection.check_and_record_vote(pubkey, &vote)?;
+        let validator = ValidatorIndex(vote.validator_id.0);
+        let signature = self.signer.sign(validator, vote_signing_epoch(vote.slot), hash_tree_root(&vote))?;
+        Ok(SignedVote { data: vote, signature })
+    }
+}
@@ -0,0 +1,281 @@
+//! Signing history of local validators, so no key ever signs a slashable pair of messages.
+//!
+//! Ask [`SlashingProtection`] before every signature; it records what it allows, see
+//! [`ProtectedSigner`](crate::ProtectedSigner). The history moves between machines in an
+//! interchange JSON format modelled on EIP-3076, which is also how it is kept on disk.
+use containers::{block::hash_tree_root, Block, Bytes32, Checkpoint, PublicKey, Slot, Vote};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::PathBuf;
+
+pub const INTERCHANGE_FORMAT_VERSION: &str = "1";
+
+/// Reason a message must not be signed, or a history not imported.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SlashingProtectionError {
+    /// Another block was already signed at this slot or a later one.
+    BlockSlotNotIncreasing { slot: Slot, highest: Slot },
+    /// Another vote was already signed at this slot or a later one.
+    VoteSlotNotIncreasing { slot: Slot, highest: Slot },
+    /// Another vote with the same target slot but a different source or target was signed.
+    DoubleVote { target_slot: Slot },
+    SurroundingVote { source: Slot, target: Slot, existing_source: Slot, existing_target: Slot },
+    SurroundedVote { source: Slot, target: Slot, existing_source: Slot, existing_target: Slot },
+    GenesisMismatch { expected: Bytes32, actual: Bytes32 },
+    UnsupportedVersion(String),
+    InvalidInterchange(String),
+    /// The history could not be read from or written to disk.
+    Io(String),
+}
+
+impl std::fmt::Display for SlashingProtectionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::BlockSlotNotIncreasing { slot, highest } =>
+                write!(f, "Block at slot {} is not after the last signed block at slot {}", slot.0, highest.0),
+            Self::VoteSlotNotIncreasing { slot, highest } =>
+                write!(f, "Vote at slot {} is not after the last signed vote at slot {}", slot.0, highest.0),
+            Self::DoubleVote { target_slot } => write!(f, "Double vote for target slot {}", target_slot.0),
+            Self::SurroundingVote { source, target, existing_source, existing_target } => write!(
+                f,
+                "Vote {}->{} surrounds signed vote {}->{}",
+                source.0, target.0, existing_source.0, existing_target.0
+            ),
+            Self::SurroundedVote { source, target, existing_source, existing_target } => write!(
+                f,
+                "Vote {}->{} is surrounded by signed vote {}->{}",
+                source.0, target.0, existing_source.0, existing_target.0
+            ),
+            Self::GenesisMismatch { expected, actual } =>
+                write!(f, "Interchange is for genesis {:?}, expected {:?}", actual.0, expected.0),
+            Self::UnsupportedVersion(version) => write!(f, "Unsupported interchange format version {}", version),
+            Self::InvalidInterchange(e) => write!(f, "Invalid interchange: {}", e),
+            Self::Io(e) => write!(f, "Slashing protection file error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for SlashingProtectionError {}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SignedBlockRecord {
+    pub slot: Slot,
+    pub signing_root: Bytes32,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SignedVoteRecord {
+    pub slot: Slot,
+    pub source: Checkpoint,
+    pub target: Checkpoint,
+    pub signing_root: Bytes32,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SigningHistory {
+    pub signed_blocks: Vec<SignedBlockRecord>,
+    pub signed_votes: Vec<SignedVoteRecord>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct InterchangeMetadata {
+    pub interchange_format_version: String,
+    /// Root of the genesis block of the chain the history belongs to.
+    pub genesis_root: Bytes32,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct InterchangeRecord {
+    pub pubkey: PublicKey,
+    pub signed_blocks: Vec<SignedBlockRecord>,
+    pub signed_votes: Vec<SignedVoteRecord>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Interchange {
+    pub metadata: InterchangeMetadata,
+    pub data: Vec<InterchangeRecord>,
+}
+
+/// Signing history of every local validator key on one chain.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SlashingProtection {
+    genesis_root: Bytes32,
+    histories: BTreeMap<PublicKey, SigningHistory>,
+    /// Interchange file rewritten after every change; memory only if `None`.
+    path: Option<PathBuf>,
+}
+
+impl SlashingProtection {
+    /// A history kept in memory only, e.g. for tests.
+    pub fn new(genesis_root: Bytes32) -> Self {
+        Self { genesis_root, histories: BTreeMap::new(), path: None }
+    }
+
+    /// Loads the history saved at `path`, if there is one, and saves every change back to it
+    /// before the change is allowed to be signed.
+    pub fn open(path: impl Into<PathBuf>, genesis_root: Bytes32) -> Result<Self, SlashingProtectionError> {
+        let path = path.into();
+        let mut protection = Self::new(genesis_root);
+        if path.exists() {
+            let json = fs::read_to_string(&path).map_err(|e| SlashingProtectionError::Io(e.to_string()))?;
+            protection.import_json(&json)?;
+        }
+        protection.path = Some(path);
+        Ok(protection)
+    }
+
+    pub fn history(&self, pubkey: &PublicKey) -> Option<&SigningHistory> {
+        self.histories.get(pubkey)
+    }
+
+    /// Records the block if `pubkey` may sign it: only blocks after the last signed one are allowed.
+    /// Re-signing the very same block is allowed as well.
+    pub fn check_and_record_block(&mut self, pubkey: &PublicKey, block: &Block) -> Result<(), SlashingProtectionError> {
+        let record = SignedBlockRecord { slot: block.slot, signing_root: hash_tree_root(block) };
+        let signed = self.histories.get(pubkey).map_or(&[][..], |history| &history.signed_blocks[..]);
+        if let Some(highest) = signed.iter().max_by_key(|signed| signed.slot) {
+            if *highest == record {
+                return Ok(());
+            }
+            if record.slot <= highest.slot {
+                return Err(SlashingProtectionError::BlockSlotNotIncreasing { slot: record.slot, highest: highest.slot });
+            }
+        }
+        self.commit(|histories| histories.entry(pubkey.clone()).or_default().signed_blocks.push(record))
+    }
+
+    /// Records the vote if `pubkey` may sign it: it must come after the last signed vote and
+    /// must neither double, surround nor be surrounded by any signed vote.
+    /// Re-signing the very same vote is allowed as well.
+    pub fn check_and_record_vote(&mut self, pubkey: &PublicKey, vote: &Vote) -> Result<(), SlashingProtectionError> {
+        let record = SignedVoteRecord {
+            slot: vote.slot,
+            source: vote.source.clone(),
+            target: vote.target.clone(),
+            signing_root: hash_tree_root(vote),
+        };
+        let signed = self.histories.get(pubkey).map_or(&[][..], |history| &history.signed_votes[..]);
+        if signed.contains(&record) {
+            return Ok(());
+        }
+        check_vote(signed, &record)?;
+        self.commit(|histories| histories.entry(pubkey.clone()).or_default().signed_votes.push(record))
+    }
+
+    pub fn export(&self) -> Interchange {
+        Interchange {
+            metadata: InterchangeMetadata {
+                interchange_format_version: INTERCHANGE_FORMAT_VERSION.to_string(),
+                genesis_root: self.genesis_root,
+            },
+            data: self
+                .histories
+                .iter()
+                .map(|(pubkey, history)| InterchangeRecord {
+                    pubkey: pubkey.clone(),
+                    signed_blocks: history.signed_blocks.clone(),
+                    signed_votes: history.signed_votes.clone(),
+                })
+                .collect(),
+        }
+    }
+
+    /// Merges another machine's history into this one. Nothing is imported if the interchange
+    /// belongs to another chain or format.
+    pub fn import(&mut self, interchange: &Interchange) -> Result<(), SlashingProtectionError> {
+        let metadata = &interchange.metadata;
+        if metadata.interchange_format_version != INTERCHANGE_FORMAT_VERSION {
+            return Err(SlashingProtectionError::UnsupportedVersion(metadata.interchange_format_version.clone()));
+        }
+        if metadata.genesis_root != self.genesis_root {
+            return Err(SlashingProtectionError::GenesisMismatch { expected: self.genesis_root, actual: metadata.genesis_root });
+        }
+
+        // Imported records are facts about signatures already out there, so they are merged as-is
+        self.commit(|histories| {
+            for record in &interchange.data {
+                let history = histories.entry(record.pubkey.clone()).or_default();
+                for block in &record.signed_blocks {
+                    if !history.signed_blocks.contains(block) {
+                        history.signed_blocks.push(block.clone());
+                    }
+                }
+                for vote in &record.signed_votes {
+                    if !history.signed_votes.contains(vote) {
+                        history.signed_votes.push(vote.clone());
+                    }
+                }
+            }
+        })
+    }
+
+    pub fn export_json(&self) -> Result<String, SlashingProtectionError> {
+        serde_json::to_string_pretty(&self.export()).map_err(|e| SlashingProtectionError::InvalidInterchange(e.to_string()))
+    }
+
+    pub fn import_json(&mut self, json: &str) -> Result<(), SlashingProtectionError> {
+        let interchange: Interchange =
+            serde_json::from_str(json).map_err(|e| SlashingProtectionError::InvalidInterchange(e.to_string()))?;
+        self.import(&interchange)
+    }
+
+    /// Applies `change` and saves it, keeping the previous history if it can't be saved, so a
+    /// retry is checked against what is actually on disk.
+    fn commit(&mut self, change: impl FnOnce(&mut BTreeMap<PublicKey, SigningHistory>)) -> Result<(), SlashingProtectionError> {
+        let previous = self.histories.clone();
+        change(&mut self.histories);
+        self.save().inspect_err(|_| self.histories = previous)
+    }
+
+    /// Writes the interchange next to `path`, syncs it and renames it over, so a crash or power
+    /// loss never leaves a truncated or stale history behind once this returns.
+    fn save(&self) -> Result<(), SlashingProtectionError> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let json = self.export_json()?;
+        let partial = path.with_extension("partial");
+        let write = || -> std::io::Result<()> {
+            let mut file = File::create(&partial)?;
+            file.write_all(json.as_bytes())?;
+            file.sync_all()?;
+            fs::rename(&partial, path)?;
+            // The rename itself is only durable once the directory entry is
+            let dir = path.parent().filter(|dir| !dir.as_os_str().is_empty()).unwrap_or(".".as_ref());
+            File::open(dir)?.sync_all()
+        };
+        write().map_err(|e| SlashingProtectionError::Io(e.to_string()))
+    }
+}
+
+fn check_vote(signed: &[SignedVoteRecord], vote: &SignedVoteRecord) -> Result<(), SlashingProtectionError> {
+    // Votes of the same slot share a one-time key epoch, so at most one may ever be signed
+    if let Some(highest) = signed.iter().map(|existing| existing.slot).max()
+        && vote.slot <= highest
+    {
+        return Err(SlashingProtectionError::VoteSlotNotIncreasing { slot: vote.slot, highest });
+    }
+
+    let (source, target) = (vote.source.slot, vote.target.slot);
+    for existing in signed {
+        let (existing_source, existing_target) = (existing.source.slot, existing.target.slot);
+        if existing_target == target && (existing.target != vote.target || existing.source != vote.source) {
+            return Err(SlashingProtectionError::DoubleVote { target_slot: target });
+        }
+        if source < existing_source && existing_target < target {
+            return Err(SlashingProtectionError::SurroundingVote { source, target, existing_source, existing_target });
+        }
+        if existing_source < source && target < existing_target {
+            return Err(SlashingProtectionError::SurroundedVote { source, target, existing_source, existing_target });
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,30 @@
+// tests/common.rs
+// Shared by every test binary; not every binary uses every helper.
+#![allow(dead_code)]
+
+use containers::{signature::Xmss, Bytes32, State, Uint64, ValidatorIndex};
+use fork_choice::Store;
+use validator::{LocalSigner, ProtectedSigner, SlashingProtection};
+
+#[allow(unused_imports)]
+pub use test_support::{checkpoint, sign_block, test_key, test_pubkeys, tick_to, vote};
+
+pub const NUM_VALIDATORS: u64 = 3;
+
+/// Signer holding every test validator's key.
+pub fn local_signer() -> LocalSigner {
+    let mut signer = LocalSigner::new(Xmss);
+    for validator in 0..NUM_VALIDATORS {
+        signer.insert(ValidatorIndex(validator), test_key(validator).1.clone());
+    }
+    signer
+}
+
+/// Every test validator's key behind a fresh, in-memory slashing protection history.
+pub fn protected_signer(genesis_root: Bytes32) -> ProtectedSigner {
+    ProtectedSigner::new(local_signer(), SlashingProtection::new(genesis_root))
+}
+
+pub fn genesis_store() -> Store {
+    Store::from_genesis(State::generate_genesis(Uint64(0), &test_pubkeys(NUM_VALIDATORS)))
+}
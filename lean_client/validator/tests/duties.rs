@@ -0,0 +1,85 @@
+// tests/duties.rs
+use chain::config::{INTERVALS_PER_SLOT, SECONDS_PER_INTERVAL};
+use containers::{ContainerConfig, Slot, ValidatorIndex};
+use validator::{vote_due_interval, Duty, ValidatorClient};
+
+const GENESIS_TIME: u64 = 1_000;
+
+fn client(validators: &[u64]) -> ValidatorClient {
+    let config = ContainerConfig { num_validators: 4, genesis_time: GENESIS_TIME };
+    ValidatorClient::new(config, validators.iter().map(|&v| ValidatorIndex(v)))
+}
+
+fn time_at(slot: u64, interval: u64) -> u64 {
+    GENESIS_TIME + (slot * INTERVALS_PER_SLOT + interval) * SECONDS_PER_INTERVAL
+}
+
+fn propose(slot: u64, validator: u64) -> Duty {
+    Duty::Propose { slot: Slot(slot), validator: ValidatorIndex(validator) }
+}
+
+fn vote(slot: u64, validator: u64) -> Duty {
+    Duty::Vote { slot: Slot(slot), validator: ValidatorIndex(validator) }
+}
+
+#[test]
+fn test_vote_is_due_halfway_through_the_slot() {
+    assert_eq!(vote_due_interval(), 2);
+}
+
+#[test]
+fn test_proposers_rotate_round_robin() {
+    let client = client(&[1, 3]);
+    assert_eq!(client.proposer(Slot(0)), None);
+    assert_eq!(client.proposer(Slot(1)), Some(ValidatorIndex(1)));
+    assert_eq!(client.proposer(Slot(2)), None);
+    assert_eq!(client.proposer(Slot(3)), Some(ValidatorIndex(3)));
+    assert_eq!(client.proposer(Slot(5)), Some(ValidatorIndex(1)));
+}
+
+#[test]
+fn test_duties_fire_at_their_intervals() {
+    let mut client = client(&[1, 3]);
+    assert_eq!(client.on_tick(GENESIS_TIME - 1), vec![]);
+
+    let mut fired = Vec::new();
+    for interval in 0..INTERVALS_PER_SLOT {
+        fired.push(client.on_tick(time_at(1, interval)));
+    }
+    assert_eq!(fired, vec![vec![propose(1, 1)], vec![], vec![vote(1, 1), vote(1, 3)], vec![]]);
+
+    // Not proposing at slot 2, but still voting
+    assert_eq!(client.on_tick(time_at(2, 0)), vec![]);
+    assert_eq!(client.on_tick(time_at(2, 2)), vec![vote(2, 1), vote(2, 3)]);
+}
+
+#[test]
+fn test_each_duty_fires_once() {
+    let mut client = client(&[0]);
+    assert_eq!(client.on_tick(time_at(4, 0)), vec![propose(4, 0)]);
+    assert_eq!(client.on_tick(time_at(4, 0)), vec![]);
+    assert_eq!(client.on_tick(time_at(4, 0) + SECONDS_PER_INTERVAL - 1), vec![]);
+    assert_eq!(client.on_tick(time_at(3, 2)), vec![]);
+}
+
+#[test]
+fn test_missed_slots_are_skipped_but_current_slot_catches_up() {
+    let mut client = client(&[0, 1]);
+    client.on_tick(time_at(1, 0));
+
+    // Jumping from slot 1 to the end of slot 5 only replays slot 5's duties
+    assert_eq!(client.on_tick(time_at(5, 3)), vec![propose(5, 1), vote(5, 0), vote(5, 1)]);
+}
+
+#[test]
+fn test_managed_validators_can_change() {
+    let mut client = client(&[]);
+    assert_eq!(client.duties_at(Slot(2), vote_due_interval()), vec![]);
+
+    assert!(client.add_validator(ValidatorIndex(2)));
+    assert!(!client.add_validator(ValidatorIndex(2)));
+    assert_eq!(client.duties_at(Slot(2), 0), vec![propose(2, 2)]);
+
+    assert!(client.remove_validator(&ValidatorIndex(2)));
+    assert_eq!(client.duties_at(Slot(2), 0), vec![]);
+}
@@ -0,0 +1,143 @@
+// tests/production.rs
+use containers::signature::SignatureError;
+use containers::{block::hash_tree_root, Bytes32, Checkpoint, Slot, StateTransitionError, ValidatorIndex, Xmss};
+use fork_choice::{get_vote_target, ForkChoiceError, Store};
+use validator::{
+    produce_block, produce_vote, LocalSigner, ProtectedSigner, SignerError, SlashingProtection, SlashingProtectionError,
+};
+
+mod common;
+use common::*;
+
+/// Produces, signs and imports the block of `slot`, returning its root.
+fn propose(store: &mut Store, slot: u64) -> containers::Bytes32 {
+    let proposer = ValidatorIndex(slot % NUM_VALIDATORS);
+    let block = produce_block(store, Slot(slot), proposer).expect("block is produced");
+    store.on_block(sign_block(block)).expect("produced block imports").root()
+}
+
+#[test]
+fn test_produced_block_builds_on_head_and_imports() {
+    let mut store = genesis_store();
+    let genesis_root = store.head;
+
+    let block = produce_block(&store, Slot(1), ValidatorIndex(1)).unwrap();
+    assert_eq!(block.parent_root, genesis_root);
+    assert_eq!(block.body.attestations.len_usize(), 0);
+
+    let root = store.on_block(sign_block(block.clone())).unwrap().root();
+    assert_eq!(store.head, root);
+    assert_eq!(hash_tree_root(&store.states[&root]), block.state_root);
+}
+
+#[test]
+fn test_produced_block_packs_pooled_votes() {
+    let mut store = genesis_store();
+    let a = propose(&mut store, 1);
+
+    tick_to(&mut store, 1, 1);
+    let anchor = store.latest_justified.clone();
+    for validator in [2, 0, 1] {
+        let target = checkpoint(&store, a);
+        store.on_attestation(&vote(validator, 1, target.clone(), target, anchor.clone())).unwrap();
+    }
+    tick_to(&mut store, 2, 0);
+
+    let block = produce_block(&store, Slot(2), ValidatorIndex(2)).unwrap();
+    let packed: Vec<u64> = (0..block.body.attestations.len_u64())
+        .map(|i| block.body.attestations.get(i).unwrap().data.validator_id.0)
+        .collect();
+    assert_eq!(packed, vec![0, 1, 2]);
+    assert_eq!(produce_block(&store, Slot(2), ValidatorIndex(2)).unwrap(), block);
+
+    let b = store.on_block(sign_block(block)).unwrap().root();
+    assert_eq!(store.states[&b].latest_justified, checkpoint(&store, a));
+
+    // The same votes would not count again now that their target is justified
+    let block = produce_block(&store, Slot(3), ValidatorIndex(0)).unwrap();
+    assert_eq!(block.body.attestations.len_usize(), 0);
+}
+
+#[test]
+fn test_produce_block_rejects_wrong_proposer() {
+    let store = genesis_store();
+    assert_eq!(
+        produce_block(&store, Slot(1), ValidatorIndex(2)),
+        Err(ForkChoiceError::StateTransition(StateTransitionError::IncorrectProposer {
+            slot: Slot(1),
+            proposer_index: ValidatorIndex(2),
+        }))
+    );
+}
+
+#[test]
+fn test_produced_vote_follows_fork_choice() {
+    let mut store = genesis_store();
+    let genesis = store.latest_justified.clone();
+    let a = propose(&mut store, 1);
+    tick_to(&mut store, 1, 2);
+
+    let mut signer = protected_signer(genesis.root);
+    let signed_vote = produce_vote(&store, Slot(1), ValidatorIndex(2), &mut signer).unwrap();
+    let vote = &signed_vote.data;
+    assert_eq!(vote.validator_id.0, 2);
+    assert_eq!(vote.slot, Slot(1));
+    assert_eq!(vote.head, Checkpoint { root: a, slot: Slot(1) });
+    assert_eq!(vote.target, get_vote_target(&store));
+    assert_eq!(vote.target, genesis);
+    assert_eq!(vote.source, genesis);
+
+    assert_eq!(produce_vote(&store, Slot(1), ValidatorIndex(2), &mut signer).unwrap(), signed_vote);
+    store.on_attestation(&signed_vote).expect("produced vote is accepted");
+}
+
+#[test]
+fn test_produce_vote_needs_a_usable_key() {
+    let store = genesis_store();
+    let mut keyless = ProtectedSigner::new(LocalSigner::new(Xmss), SlashingProtection::new(store.head));
+    assert_eq!(
+        produce_vote(&store, Slot(0), ValidatorIndex(1), &mut keyless),
+        Err(SignerError::UnknownValidator(ValidatorIndex(1)))
+    );
+    assert_eq!(
+        produce_vote(&store, Slot(32), ValidatorIndex(1), &mut protected_signer(store.head)),
+        Err(SignerError::Signature(SignatureError::EpochOutOfRange { epoch: 65, lifetime: 64 }))
+    );
+}
+
+#[test]
+fn test_protected_signer_refuses_conflicting_messages() {
+    let mut store = genesis_store();
+    let mut signer = protected_signer(store.head);
+    let pubkey = |validator: u64| test_key(validator).0.clone();
+
+    let block = produce_block(&store, Slot(1), ValidatorIndex(1)).unwrap();
+    let signed_block = signer.sign_block(&pubkey(1), block.clone()).unwrap();
+    assert_eq!(signed_block, sign_block(block.clone()));
+    let a = store.on_block(signed_block).unwrap().root();
+
+    let mut conflicting = block;
+    conflicting.state_root = Bytes32::default();
+    assert_eq!(
+        signer.sign_block(&pubkey(1), conflicting),
+        Err(SignerError::SlashingProtection(SlashingProtectionError::BlockSlotNotIncreasing {
+            slot: Slot(1),
+            highest: Slot(1),
+        }))
+    );
+
+    tick_to(&mut store, 1, 2);
+    let signed_vote = produce_vote(&store, Slot(1), ValidatorIndex(2), &mut signer).unwrap();
+    assert_eq!(signed_vote.data.head, checkpoint(&store, a));
+
+    let mut other_head = signed_vote.data;
+    other_head.head = store.latest_justified.clone();
+    assert_eq!(
+        signer.sign_vote(&pubkey(2), other_head),
+        Err(SignerError::SlashingProtection(SlashingProtectionError::VoteSlotNotIncreasing {
+            slot: Slot(1),
+            highest: Slot(1),
+        }))
+    );
+    assert_eq!(signer.protection().history(&pubkey(2)).unwrap().signed_votes.len(), 1);
+}
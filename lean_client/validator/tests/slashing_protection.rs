@@ -0,0 +1,173 @@
+// tests/slashing_protection.rs
+use containers::ssz::H256;
+use containers::{Block, Bytes32, Checkpoint, PublicKey, Slot, Uint64, ValidatorIndex, Vote};
+use validator::{SlashingProtection, SlashingProtectionError};
+
+fn pubkey(byte: u8) -> PublicKey {
+    PublicKey { root: Bytes32(H256::repeat_byte(byte)), public_seed: Bytes32(H256::repeat_byte(byte)) }
+}
+
+fn root(byte: u8) -> Bytes32 {
+    Bytes32(H256::repeat_byte(byte))
+}
+
+fn genesis_root() -> Bytes32 {
+    root(0xaa)
+}
+
+fn block(slot: u64, parent: u8) -> Block {
+    Block { slot: Slot(slot), proposer_index: ValidatorIndex(0), parent_root: root(parent), ..Block::default() }
+}
+
+fn checkpoint(slot: u64, byte: u8) -> Checkpoint {
+    Checkpoint { root: root(byte), slot: Slot(slot) }
+}
+
+fn vote(slot: u64, source: Checkpoint, target: Checkpoint) -> Vote {
+    Vote { validator_id: Uint64(0), slot: Slot(slot), head: target.clone(), target, source }
+}
+
+#[test]
+fn test_blocks_must_advance() {
+    let mut db = SlashingProtection::new(genesis_root());
+    let key = pubkey(1);
+
+    assert_eq!(db.check_and_record_block(&key, &block(2, 1)), Ok(()));
+    assert_eq!(db.check_and_record_block(&key, &block(2, 1)), Ok(()));
+    assert_eq!(
+        db.check_and_record_block(&key, &block(2, 2)),
+        Err(SlashingProtectionError::BlockSlotNotIncreasing { slot: Slot(2), highest: Slot(2) })
+    );
+    assert_eq!(
+        db.check_and_record_block(&key, &block(1, 1)),
+        Err(SlashingProtectionError::BlockSlotNotIncreasing { slot: Slot(1), highest: Slot(2) })
+    );
+    assert_eq!(db.check_and_record_block(&key, &block(3, 2)), Ok(()));
+
+    // Other keys have their own history
+    assert_eq!(db.check_and_record_block(&pubkey(2), &block(1, 1)), Ok(()));
+    assert_eq!(db.history(&key).unwrap().signed_blocks.len(), 2);
+}
+
+#[test]
+fn test_double_and_surround_votes_are_refused() {
+    let mut db = SlashingProtection::new(genesis_root());
+    let key = pubkey(1);
+    let first = vote(4, checkpoint(2, 2), checkpoint(4, 4));
+
+    assert_eq!(db.check_and_record_vote(&key, &first), Ok(()));
+    assert_eq!(db.check_and_record_vote(&key, &first), Ok(()));
+
+    // Another vote in the same slot would reuse the one-time key
+    assert_eq!(
+        db.check_and_record_vote(&key, &vote(4, checkpoint(2, 2), checkpoint(3, 3))),
+        Err(SlashingProtectionError::VoteSlotNotIncreasing { slot: Slot(4), highest: Slot(4) })
+    );
+    assert_eq!(
+        db.check_and_record_vote(&key, &vote(5, checkpoint(2, 2), checkpoint(4, 9))),
+        Err(SlashingProtectionError::DoubleVote { target_slot: Slot(4) })
+    );
+    assert_eq!(
+        db.check_and_record_vote(&key, &vote(5, checkpoint(1, 1), checkpoint(5, 5))),
+        Err(SlashingProtectionError::SurroundingVote {
+            source: Slot(1),
+            target: Slot(5),
+            existing_source: Slot(2),
+            existing_target: Slot(4),
+        })
+    );
+    assert_eq!(
+        db.check_and_record_vote(&key, &vote(5, checkpoint(3, 3), checkpoint(3, 3))),
+        Err(SlashingProtectionError::SurroundedVote {
+            source: Slot(3),
+            target: Slot(3),
+            existing_source: Slot(2),
+            existing_target: Slot(4),
+        })
+    );
+
+    // Voting again for the same target, or moving on, is fine
+    assert_eq!(db.check_and_record_vote(&key, &vote(5, checkpoint(2, 2), checkpoint(4, 4))), Ok(()));
+    assert_eq!(db.check_and_record_vote(&key, &vote(6, checkpoint(4, 4), checkpoint(6, 6))), Ok(()));
+    assert_eq!(db.history(&key).unwrap().signed_votes.len(), 3);
+}
+
+#[test]
+fn test_interchange_round_trip_carries_protection() {
+    let mut db = SlashingProtection::new(genesis_root());
+    db.check_and_record_block(&pubkey(1), &block(3, 1)).unwrap();
+    db.check_and_record_vote(&pubkey(1), &vote(3, checkpoint(0, 0), checkpoint(2, 2))).unwrap();
+    db.check_and_record_vote(&pubkey(2), &vote(3, checkpoint(0, 0), checkpoint(3, 3))).unwrap();
+
+    let json = db.export_json().unwrap();
+    let mut moved = SlashingProtection::new(genesis_root());
+    moved.import_json(&json).unwrap();
+    assert_eq!(moved, db);
+
+    // Importing twice changes nothing
+    moved.import_json(&json).unwrap();
+    assert_eq!(moved, db);
+
+    assert!(moved.check_and_record_block(&pubkey(1), &block(3, 2)).is_err());
+    assert!(moved.check_and_record_vote(&pubkey(2), &vote(4, checkpoint(0, 0), checkpoint(3, 9))).is_err());
+}
+
+#[test]
+fn test_import_rejects_foreign_or_malformed_interchange() {
+    let db = SlashingProtection::new(genesis_root());
+    let mut other_chain = SlashingProtection::new(root(0xbb));
+    assert_eq!(
+        other_chain.import(&db.export()),
+        Err(SlashingProtectionError::GenesisMismatch { expected: root(0xbb), actual: genesis_root() })
+    );
+
+    let mut interchange = db.export();
+    interchange.metadata.interchange_format_version = "5".to_string();
+    let mut target = SlashingProtection::new(genesis_root());
+    assert_eq!(target.import(&interchange), Err(SlashingProtectionError::UnsupportedVersion("5".to_string())));
+
+    let malformed = db.export_json().unwrap().replace("\"data\"", "\"records\"");
+    assert!(matches!(target.import_json(&malformed), Err(SlashingProtectionError::InvalidInterchange(_))));
+}
+
+#[test]
+fn test_opened_history_is_saved_after_every_record() {
+    let path = std::env::temp_dir().join(format!("slashing_protection_{}.json", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+
+    let mut db = SlashingProtection::open(&path, genesis_root()).unwrap();
+    db.check_and_record_block(&pubkey(1), &block(3, 1)).unwrap();
+    db.check_and_record_vote(&pubkey(1), &vote(3, checkpoint(0, 0), checkpoint(2, 2))).unwrap();
+
+    // A restart picks up where the last signature left off
+    let mut reopened = SlashingProtection::open(&path, genesis_root()).unwrap();
+    assert_eq!(reopened.export(), db.export());
+    assert!(reopened.check_and_record_block(&pubkey(1), &block(3, 2)).is_err());
+    assert_eq!(reopened.check_and_record_vote(&pubkey(1), &vote(4, checkpoint(2, 2), checkpoint(4, 4))), Ok(()));
+
+    let mut saved = SlashingProtection::new(genesis_root());
+    saved.import_json(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    assert_eq!(saved.history(&pubkey(1)).unwrap().signed_votes.len(), 2);
+
+    assert!(matches!(
+        SlashingProtection::open(&path, root(0xbb)),
+        Err(SlashingProtectionError::GenesisMismatch { .. })
+    ));
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_record_is_dropped_when_it_cannot_be_saved() {
+    let path = std::env::temp_dir().join(format!("slashing_protection_missing_{}", std::process::id())).join("history.json");
+    let mut db = SlashingProtection::open(&path, genesis_root()).unwrap();
+
+    // Retrying must not find the unsaved record and sign without protection
+    for _ in 0..2 {
+        assert!(matches!(db.check_and_record_block(&pubkey(1), &block(3, 1)), Err(SlashingProtectionError::Io(_))));
+        assert!(matches!(
+            db.check_and_record_vote(&pubkey(1), &vote(3, checkpoint(0, 0), checkpoint(2, 2))),
+            Err(SlashingProtectionError::Io(_))
+        ));
+    }
+    assert_eq!(db.history(&pubkey(1)), None);
+}